@@ -1,11 +1,27 @@
-use clap::Parser;
+pub mod markup;
+pub mod memory;
+
+use clap::{error::ErrorKind, CommandFactory, Parser};
 use log::{debug, LevelFilter};
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use opencc_rust::{DefaultConfig, OpenCC};
-use sqlx::{mysql::MySqlConnectOptions, ConnectOptions, Encode, MySql, MySqlPool, Type};
-use std::{future::Future, ops::Not, str::FromStr};
+use sqlx::{
+  mysql::{MySqlConnectOptions, MySqlPoolOptions},
+  ConnectOptions, Encode, MySql, MySqlPool, Type,
+};
+use std::{future::Future, num::NonZeroUsize, path::PathBuf, str::FromStr, thread};
 use strum::ParseError;
 
+use crate::{
+  common::memory::{MemoryProfile, TranslationMemory},
+  data::{Glossary, Registry},
+  translate::{
+    filter::{apply_filters, RowFilter},
+    worker::ConvertPool,
+    TranslateTarget,
+  },
+};
+
 /// Run aysnc method as sync (block thread and wait result).
 pub fn block_async<F>(f: F) -> F::Output
 where
@@ -43,32 +59,221 @@ pub enum ServerType {
   AzerothCore,
 }
 
-/// Define the language types.
+/// Define the action when a translate batch makes no progress.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum StallAction {
+  /// Abort the translation with an error
+  Abort,
+  /// Log a warning and continue with the next batch
+  Skip,
+}
+
+/// Define the scope of the transaction which the translated rows are written in.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum TransactionScope {
+  /// Commit each batch separately
+  Batch,
+  /// Commit each table after all its batches are written
+  Table,
+  /// Commit once after the whole run, any failure rolls back everything
+  Run,
+}
+
+/// Define the file format of the check and diff reports.
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum ReportFormat {
+  Json,
+  Csv,
+}
+
+/// Define the language types, all the WOW client locales.
 #[derive(
-  Clone, Copy, Debug, Hash, PartialEq, Eq, strum_macros::Display, strum_macros::EnumString,
+  Clone,
+  Copy,
+  Debug,
+  Hash,
+  PartialEq,
+  Eq,
+  serde::Serialize,
+  serde::Deserialize,
+  strum_macros::Display,
+  strum_macros::EnumString,
+  strum_macros::EnumIter,
 )]
 pub enum Language {
+  #[strum(to_string = "enUS")]
+  #[serde(rename = "enUS")]
+  English,
+  #[strum(to_string = "koKR")]
+  #[serde(rename = "koKR")]
+  Korean,
+  #[strum(to_string = "frFR")]
+  #[serde(rename = "frFR")]
+  French,
+  #[strum(to_string = "deDE")]
+  #[serde(rename = "deDE")]
+  German,
   #[strum(to_string = "zhCN")]
+  #[serde(rename = "zhCN")]
   Chinese,
   #[strum(to_string = "zhTW")]
+  #[serde(rename = "zhTW")]
   Taiwanese,
+  #[strum(to_string = "esES")]
+  #[serde(rename = "esES")]
+  Spanish,
+  #[strum(to_string = "esMX")]
+  #[serde(rename = "esMX")]
+  Mexican,
+  #[strum(to_string = "ruRU")]
+  #[serde(rename = "ruRU")]
+  Russian,
 }
 
-impl From<Language> for &OpenCC {
-  fn from(value: Language) -> Self {
-    match value {
-      Language::Taiwanese => &OPECC_TW2SP,
-      Language::Chinese => &OPECC_S2TWP,
+impl Language {
+  /// The locale index used by the column per locale tables (MaNGOS style),
+  /// the enUS texts are stored in the base columns (index 0) and the other locales in `loc1` ~ `loc8`.
+  pub fn locale_index(self) -> u8 {
+    match self {
+      Self::English => 0,
+      Self::Korean => 1,
+      Self::French => 2,
+      Self::German => 3,
+      Self::Chinese => 4,
+      Self::Taiwanese => 5,
+      Self::Spanish => 6,
+      Self::Mexican => 7,
+      Self::Russian => 8,
     }
   }
 }
 
-impl Not for Language {
-  type Output = Self;
-  fn not(self) -> Self {
-    match self {
-      Self::Chinese => Self::Taiwanese,
-      Self::Taiwanese => Self::Chinese,
+/// Define the translation direction from the source locale to the target locale,
+/// the pair can only be created by `LocalePair::new` (or deserialized) so it's always supported.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "RawLocalePair")]
+pub struct LocalePair {
+  source: Language,
+  target: Language,
+}
+
+/// The unchecked locale pair of the plan file, it's validated when the plan is loaded.
+#[derive(serde::Deserialize)]
+struct RawLocalePair {
+  source: Language,
+  target: Language,
+}
+
+impl TryFrom<RawLocalePair> for LocalePair {
+  type Error = anyhow::Error;
+
+  fn try_from(value: RawLocalePair) -> anyhow::Result<Self> {
+    Self::new(value.source, value.target)
+  }
+}
+
+impl LocalePair {
+  /// The pairs which can be converted by OpenCC, zhTW -> zhCN is translated first.
+  pub const CHINESE: [LocalePair; 2] = [
+    LocalePair {
+      source: Language::Taiwanese,
+      target: Language::Chinese,
+    },
+    LocalePair {
+      source: Language::Chinese,
+      target: Language::Taiwanese,
+    },
+  ];
+
+  /// Create the pair, only the pairs which can be converted by OpenCC are supported.
+  pub fn new(source: Language, target: Language) -> anyhow::Result<Self> {
+    let pair = LocalePair { source, target };
+    if !Self::CHINESE.contains(&pair) {
+      anyhow::bail!("Translating from {source} to {target} isn't supported!");
+    }
+    Ok(pair)
+  }
+
+  /// The locale which the texts are converted from.
+  pub fn source(self) -> Language {
+    self.source
+  }
+
+  /// The locale which the converted texts are written to.
+  pub fn target(self) -> Language {
+    self.target
+  }
+
+  /// Get the supported pair which translates from the source locale.
+  pub fn from_source(source: Language) -> anyhow::Result<Self> {
+    Self::CHINESE
+      .into_iter()
+      .find(|v| v.source == source)
+      .ok_or_else(|| anyhow::anyhow!("Translating from {source} isn't supported!"))
+  }
+
+  /// The OpenCC config name of the pair, it's a part of the translation memory profile.
+  fn opencc_config(self) -> &'static str {
+    match (self.source, self.target) {
+      (Language::Taiwanese, Language::Chinese) => "tw2sp",
+      (Language::Chinese, Language::Taiwanese) => "s2twp",
+      _ => unreachable!("OpenCC can't convert the texts from {self}"),
+    }
+  }
+
+  /// Convert the text by the glossary and OpenCC, the WoW markups are kept as is.
+  fn convert_str(self, text: &str) -> String {
+    let opencc: &OpenCC = self.into();
+    match GLOSSARY.get() {
+      Some(glossary) => {
+        markup::convert_markup(text, |v| glossary.convert(self, v, |v| opencc.convert(v)))
+      }
+      None => markup::convert_markup(text, |v| opencc.convert(v)),
+    }
+  }
+}
+
+/// Define which locale is the source of truth of the table, the other locale is only generated from it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum TranslatePolicy {
+  /// Only translate from zhCN to zhTW
+  #[serde(rename = "zhCN")]
+  FromChinese,
+  /// Only translate from zhTW to zhCN
+  #[serde(rename = "zhTW")]
+  FromTaiwanese,
+  /// Translate from whichever locale exists
+  #[default]
+  #[serde(rename = "either")]
+  Either,
+}
+
+impl TranslatePolicy {
+  /// The locale pairs which are allowed by the policy.
+  pub fn pairs(self) -> Vec<LocalePair> {
+    LocalePair::CHINESE
+      .into_iter()
+      .filter(|v| match self {
+        Self::FromChinese => v.source == Language::Chinese,
+        Self::FromTaiwanese => v.source == Language::Taiwanese,
+        Self::Either => true,
+      })
+      .collect()
+  }
+}
+
+impl std::fmt::Display for LocalePair {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{} -> {}", self.source, self.target)
+  }
+}
+
+impl From<LocalePair> for &OpenCC {
+  fn from(value: LocalePair) -> Self {
+    match (value.source, value.target) {
+      (Language::Taiwanese, Language::Chinese) => &OPECC_TW2SP,
+      (Language::Chinese, Language::Taiwanese) => &OPECC_S2TWP,
+      _ => unreachable!("OpenCC can't convert the texts from {value}"),
     }
   }
 }
@@ -99,9 +304,10 @@ impl TryFrom<String> for Language {
 pub trait ConvertText {
   fn convert_text(&self, text: &Option<String>) -> String;
 
-  /// Try to convert option string text.
+  /// Try to convert option string text, the WoW markups are kept as is.
   fn convert_impl<'a>(opencc: impl Into<&'a OpenCC>, text: &Option<String>) -> String {
-    opencc.into().convert(text.as_ref().unwrap_or(&"".into()))
+    let opencc = opencc.into();
+    markup::convert_markup(text.as_deref().unwrap_or_default(), |v| opencc.convert(v))
   }
 }
 
@@ -111,9 +317,14 @@ impl ConvertText for OpenCC {
   }
 }
 
-impl ConvertText for Language {
+impl ConvertText for LocalePair {
+  // The repeated texts are only converted once by the translation memory.
   fn convert_text(&self, text: &Option<String>) -> String {
-    Self::convert_impl(*self, text)
+    let text = text.as_deref().unwrap_or_default();
+    match MEMORY.get() {
+      Some(memory) => memory.convert(*self, text, |v| self.convert_str(v)),
+      None => self.convert_str(text),
+    }
   }
 }
 
@@ -136,21 +347,169 @@ pub struct CommandLine {
   /// Set the database login password
   #[arg(short, long, default_value = "password")]
   pub password: String,
-  /// Set the data batch size
+  /// Set the data batch size of the check, diff and plan queries (the translate pipeline pages and writes fixed chunks)
   #[arg(short, long, default_value = "1000")]
   pub batch_size: usize,
-  /// Enable async execute
+  /// Set the action when a translate batch makes no progress
+  #[arg(long, value_enum, default_value = "abort")]
+  pub on_stall: StallAction,
+  /// Set the scope of the transaction which the translated rows are written in
+  #[arg(long, value_enum, default_value = "batch")]
+  pub transaction: TransactionScope,
+  /// Run as many tables (or key ranges) at once as the max connections count allows (instead of --jobs)
   #[arg(short, long)]
   pub r#async: bool,
+  /// Set the count of the tables (or key ranges) which are processed at once (at most 64)
+  #[arg(short, long, default_value = "1")]
+  pub jobs: NonZeroUsize,
+  /// Split each table into the key ranges of the first key column which are translated as separate jobs (integer keys only)
+  #[arg(long, default_value = "1")]
+  pub key_ranges: NonZeroUsize,
+  /// Set the max connections count of the MySQL pool, each job needs 1 connection to read, 2 to translate and 3 to overwrite (default is the connections of all the jobs, at least 10)
+  #[arg(long)]
+  pub max_connections: Option<u32>,
+  /// Set the worker threads count of the text conversion, default is the available CPU cores count
+  #[arg(long)]
+  pub convert_threads: Option<NonZeroUsize>,
   /// Run database translation check
   #[arg(short, long)]
   pub check: Option<ServerType>,
+  /// Write the check or diff report to the file (with --check or --diff)
+  #[arg(long)]
+  pub report: Option<PathBuf>,
+  /// Set the check or diff report file format
+  #[arg(long, value_enum, default_value = "json")]
+  pub report_format: ReportFormat,
+  /// Run the consistency diff between the existing zhCN and zhTW texts
+  #[arg(long)]
+  pub diff: Option<ServerType>,
   /// Execute database translate
   #[arg(short, long)]
   pub translate: Option<ServerType>,
+  /// Overwrite the existing target locale texts by converting the source locale texts (zhCN or zhTW)
+  #[arg(long)]
+  pub overwrite: Option<Language>,
+  /// Only translate from the source locale (zhCN or zhTW), it narrows the table policies
+  #[arg(long)]
+  pub from: Option<Language>,
+  /// Only translate to the target locale (zhCN or zhTW), it narrows the table policies
+  #[arg(long)]
+  pub to: Option<Language>,
+  /// Write the translate SQL to the file instead of executing it (with --translate)
+  #[arg(long)]
+  pub export: Option<PathBuf>,
+  /// Translate the tables in the SQL dump files offline instead of the database (can be repeated, with --translate and --export)
+  #[arg(long)]
+  pub dump: Vec<PathBuf>,
+  /// Write the rows which would be translated to the plan file instead of executing translate
+  #[arg(long)]
+  pub plan: Option<ServerType>,
+  /// Execute exactly the translate plan in the plan file
+  #[arg(long)]
+  pub apply: bool,
+  /// Set the translate plan file path
+  #[arg(long, default_value = "translate_plan.json")]
+  pub plan_file: PathBuf,
   /// Set the log level filter
   #[arg(short, long, default_value = "info")]
   pub log: LevelFilter,
+  /// Only process the tables which match the names or glob patterns, e.g. quest_* (can be repeated or comma separated)
+  #[arg(long, value_delimiter = ',')]
+  pub tables: Vec<String>,
+  /// Skip the tables which match the names or glob patterns (can be repeated or comma separated)
+  #[arg(long, value_delimiter = ',')]
+  pub exclude_tables: Vec<String>,
+  /// Only translate the rows which match the conditions on the key or extra columns,
+  /// e.g. "quest_template_locale:ID BETWEEN 26000 AND 26100" (can be repeated)
+  #[arg(long = "where", value_name = "[TABLE:]CONDITION")]
+  pub r#where: Vec<RowFilter>,
+  /// Load extra table definitions from the registry file (can be repeated)
+  #[arg(short, long)]
+  pub registry: Vec<PathBuf>,
+  /// Load the glossary phrases which take priority over OpenCC from the glossary file (can be repeated)
+  #[arg(long)]
+  pub glossary: Vec<PathBuf>,
+  /// Persist the translation memory of the converted short texts to the file between runs
+  #[arg(long)]
+  pub memory_file: Option<PathBuf>,
+}
+
+/// The max count of the jobs which run at once.
+const MAX_JOBS: usize = 64;
+/// The default max connections count of the MySQL pool (the sqlx default),
+/// it's also the connections budget of --async without --max-connections.
+const DEFAULT_MAX_CONNECTIONS: u32 = 10;
+
+impl CommandLine {
+  /// The connections count which each job holds at once: the read-only modes only use one connection,
+  /// the translate pipeline reads a page while its write transaction holds another connection,
+  /// and the overwrite diff fetches the translated rows by a third connection inside the write transaction.
+  fn connections_per_job(&self) -> u32 {
+    let writes =
+      (self.translate.is_some() && self.export.is_none() && self.dump.is_empty()) || self.apply;
+    match (writes, self.overwrite.is_some() && self.translate.is_some()) {
+      (false, _) => 1,
+      (true, false) => 2,
+      (true, true) => 3,
+    }
+  }
+
+  /// The count of the jobs which run at once, --async runs as many jobs as the max connections count allows.
+  pub fn jobs(&self) -> usize {
+    if self.r#async {
+      let max_connections = self.max_connections.unwrap_or(DEFAULT_MAX_CONNECTIONS);
+      ((max_connections / self.connections_per_job()) as usize).clamp(1, MAX_JOBS)
+    } else {
+      self.jobs.get()
+    }
+  }
+
+  /// The max connections count of the MySQL pool, default is the connections of all the jobs (at least 10).
+  fn max_connections(&self) -> u32 {
+    self.max_connections.unwrap_or_else(|| {
+      (self.jobs() as u32 * self.connections_per_job()).max(DEFAULT_MAX_CONNECTIONS)
+    })
+  }
+
+  /// Validate the options which depend on each other.
+  fn validate(&self) -> Result<(), String> {
+    if self.jobs.get() > MAX_JOBS {
+      return Err(format!(
+        "--jobs {} exceeds the max jobs count {MAX_JOBS}",
+        self.jobs
+      ));
+    }
+    // The key ranges of a table are translated as separate jobs, so they can't share the table transaction.
+    if matches!(self.transaction, TransactionScope::Table) && self.key_ranges.get() > 1 {
+      return Err(format!(
+        "--transaction table can't commit the {} key ranges of a table together, use --transaction run or batch instead",
+        self.key_ranges
+      ));
+    }
+    // Each job holds its connections at once, so the jobs with too few connections wait for each other
+    // until the acquire timeout.
+    let connections_per_job = self.connections_per_job();
+    let min_connections = self.jobs() as u32 * connections_per_job;
+    if self.max_connections() < min_connections {
+      return Err(format!(
+        "--max-connections {} is fewer than the {min_connections} connections of {} jobs ({connections_per_job} connections per job)",
+        self.max_connections(),
+        self.jobs()
+      ));
+    }
+    Ok(())
+  }
+
+  /// Parse the command line arguments, exit with the usage error when the options are invalid.
+  fn parse_valid() -> Self {
+    let command_line = Self::parse();
+    if let Err(message) = command_line.validate() {
+      Self::command()
+        .error(ErrorKind::ArgumentConflict, message)
+        .exit();
+    }
+    command_line
+  }
 }
 
 /// OpenCC configs.
@@ -160,7 +519,78 @@ static OPECC_TW2SP: Lazy<OpenCC> =
   Lazy::new(|| OpenCC::new(DefaultConfig::TW2SP).expect("Init OpenCC error!"));
 
 /// Global lazy instances.
-pub static COMMAND_LINE: Lazy<CommandLine> = Lazy::new(|| CommandLine::parse());
+pub static COMMAND_LINE: Lazy<CommandLine> = Lazy::new(CommandLine::parse_valid);
+/// Get the translate targets of the server type selected by the --tables and --exclude-tables patterns,
+/// the --where row filters are added to the targets.
+pub fn selected_targets(server_type: &ServerType) -> anyhow::Result<Vec<TranslateTarget>> {
+  let mut targets = REGISTRY.select_targets(
+    server_type,
+    &COMMAND_LINE.tables,
+    &COMMAND_LINE.exclude_tables,
+  )?;
+  apply_filters(&mut targets, &COMMAND_LINE.r#where)?;
+  Ok(targets)
+}
+
+/// The glossary is only used after it's loaded, so the conversion works without the command line (e.g. in tests).
+static GLOSSARY: OnceCell<Glossary> = OnceCell::new();
+
+/// Load the glossary files of the command line.
+pub fn init_glossary() -> anyhow::Result<()> {
+  let glossary = Glossary::load(&COMMAND_LINE.glossary)?;
+  // Ignore the glossary which is already loaded.
+  let _ = GLOSSARY.set(glossary);
+  Ok(())
+}
+
+/// The translation memory is only used after it's initialized, like the glossary.
+static MEMORY: OnceCell<TranslationMemory> = OnceCell::new();
+/// Bump the version when the conversion logic (e.g. the markup rules) changes,
+/// so the persisted translation memory entries are invalidated.
+const MEMORY_PROFILE_VERSION: u32 = 2;
+
+/// Init the translation memory after the glossary is loaded, the memory is always used in the process
+/// and the entries of the memory file are loaded when it's set.
+/// The profile of each direction contains the OpenCC config and the glossary terms.
+pub fn init_memory() -> anyhow::Result<()> {
+  let profiles = LocalePair::CHINESE
+    .into_iter()
+    .map(|pair| MemoryProfile {
+      pair,
+      converter: format!("v{MEMORY_PROFILE_VERSION}:{}", pair.opencc_config()),
+      terms: GLOSSARY.get().map(|v| v.phrases(pair)).unwrap_or_default(),
+    })
+    .collect();
+  let mut memory = TranslationMemory::new(profiles);
+  if let Some(path) = &COMMAND_LINE.memory_file {
+    memory.load(path)?;
+  }
+  // Ignore the memory which is already initialized.
+  let _ = MEMORY.set(memory);
+  Ok(())
+}
+
+/// Report the translation memory hit rate and persist it to the memory file.
+pub fn save_memory() -> anyhow::Result<()> {
+  if let Some(memory) = MEMORY.get() {
+    memory.report();
+    if let Some(path) = &COMMAND_LINE.memory_file {
+      memory.save(path)?;
+    }
+  }
+  Ok(())
+}
+
+pub static REGISTRY: Lazy<Registry> =
+  Lazy::new(|| Registry::load(&COMMAND_LINE.registry).expect("Load table registry error!"));
+pub static CONVERT_POOL: Lazy<ConvertPool> = Lazy::new(|| {
+  let threads = COMMAND_LINE
+    .convert_threads
+    .or_else(|| thread::available_parallelism().ok())
+    .map_or(1, NonZeroUsize::get);
+  debug!("Start {threads} convert worker threads ...");
+  ConvertPool::new(threads)
+});
 pub static POOL: Lazy<MySqlPool> = Lazy::new(|| {
   block_async({
     let options = MySqlConnectOptions::new()
@@ -169,7 +599,62 @@ pub static POOL: Lazy<MySqlPool> = Lazy::new(|| {
       .username(&COMMAND_LINE.username)
       .password(&COMMAND_LINE.password)
       .log_statements(LevelFilter::Debug);
-    MySqlPool::connect_with(options)
+    let max_connections = COMMAND_LINE.max_connections();
+    debug!("Connect MySQL with max connections count {max_connections} ...");
+    MySqlPoolOptions::new()
+      .max_connections(max_connections)
+      .connect_with(options)
   })
   .expect("Init MySQL connection error!")
 });
+
+#[test]
+fn command_line_validate_test() {
+  let validate = |args: &[&str]| {
+    CommandLine::try_parse_from([&["wow-database-translator"], args].concat())
+      .unwrap()
+      .validate()
+  };
+  assert!(validate(&[]).is_ok());
+  // The check jobs need 1 connection each, the translate jobs need 2 and the overwrite jobs need 3.
+  assert!(validate(&[
+    "--check",
+    "mangos0",
+    "--jobs",
+    "4",
+    "--max-connections",
+    "4"
+  ])
+  .is_ok());
+  let translate = [
+    "--translate",
+    "azeroth-core",
+    "--jobs",
+    "4",
+    "--max-connections",
+  ];
+  assert!(validate(&[&translate[..], &["8"]].concat()).is_ok());
+  assert!(validate(&[&translate[..], &["7"]].concat()).is_err());
+  assert!(validate(&[&translate[..], &["11", "--overwrite", "zhCN"]].concat()).is_err());
+  assert!(validate(&[&translate[..], &["12", "--overwrite", "zhCN"]].concat()).is_ok());
+  // --async runs as many jobs as the connections allow.
+  let command_line = CommandLine::try_parse_from([
+    "wow-database-translator",
+    "--translate",
+    "azeroth-core",
+    "--async",
+    "--max-connections",
+    "64",
+  ])
+  .unwrap();
+  assert!(command_line.validate().is_ok());
+  assert_eq!(command_line.jobs(), 32);
+  let command_line =
+    CommandLine::try_parse_from(["wow-database-translator", "--check", "mangos0", "--async"])
+      .unwrap();
+  assert_eq!(command_line.jobs(), 10);
+  assert_eq!(command_line.max_connections(), 10);
+  assert!(validate(&["--jobs", "65"]).is_err());
+  assert!(validate(&["--transaction", "table", "--key-ranges", "2"]).is_err());
+  assert!(validate(&["--transaction", "run", "--key-ranges", "2"]).is_ok());
+}
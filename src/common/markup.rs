@@ -0,0 +1,135 @@
+/// The token of the text, only the human text is converted and the markup is kept as is.
+#[derive(Debug, PartialEq, Eq)]
+enum Token<'a> {
+  Text(&'a str),
+  Markup(&'a str),
+}
+
+/// Get the end of the markup which starts at the position, the markups are all ASCII,
+/// so the byte positions next to them are always the char boundaries.
+fn markup_end(bytes: &[u8], i: usize) -> Option<usize> {
+  let find = |pattern: &[u8]| {
+    bytes[i + 2..]
+      .windows(pattern.len())
+      .position(|v| v == pattern)
+      .map(|v| i + 2 + v + pattern.len())
+  };
+  match (bytes[i], *bytes.get(i + 1)?) {
+    // The placeholders of the player name, race, class and the line break: $N, $R, $C, $B ...
+    (b'$', v) if v.is_ascii_alphanumeric() => Some(i + 2),
+    (b'%', b'%') => Some(i + 2),
+    // The format arguments: %s, %d, %5.1f, %1$s ...
+    (b'%', _) => {
+      let end = i
+        + 1
+        + bytes[i + 1..]
+          .iter()
+          .position(|v| !matches!(v, b'0'..=b'9' | b'.' | b'$' | b'-' | b'+'))?;
+      matches!(
+        bytes[end],
+        b's' | b'd' | b'i' | b'u' | b'f' | b'x' | b'X' | b'c'
+      )
+      .then_some(end + 1)
+    }
+    // The colour code: |cAARRGGBB ... |r
+    (b'|', b'c') => bytes
+      .get(i + 2..i + 10)
+      .filter(|v| v.iter().all(u8::is_ascii_hexdigit))
+      .map(|_| i + 10),
+    // The hyperlink and texture: |Hitem:...|h[...]|h and |T...|t
+    (b'|', b'H') => find(b"|h"),
+    (b'|', b'T') => find(b"|t"),
+    (b'|', b'r' | b'h' | b'n' | b'|') => Some(i + 2),
+    _ => None,
+  }
+}
+
+/// Get the end of the gender or plural choice, e.g. `$g男:女;`, the choice must contain `:`.
+fn choice_end(bytes: &[u8], i: usize) -> Option<usize> {
+  if bytes[i] != b'$' || !matches!(bytes[i + 1], b'g' | b'G' | b'l' | b'L') {
+    return None;
+  }
+  let end = i + 2 + bytes[i + 2..].iter().position(|v| *v == b';')?;
+  bytes[i + 2..end].contains(&b':').then_some(end + 1)
+}
+
+fn tokenize(text: &str) -> Vec<Token<'_>> {
+  let bytes = text.as_bytes();
+  let mut tokens = vec![];
+  // The start of the human text which isn't pushed.
+  let mut start = 0;
+  let mut i = 0;
+
+  while i < bytes.len() {
+    let Some(end) = markup_end(bytes, i) else {
+      i += 1;
+      continue;
+    };
+    if start < i {
+      tokens.push(Token::Text(&text[start..i]));
+    }
+    match choice_end(bytes, i) {
+      // The choices are the human texts which are separated by the markups.
+      Some(choice_end) => {
+        tokens.push(Token::Markup(&text[i..end]));
+        for (index, choice) in text[end..choice_end - 1].split(':').enumerate() {
+          if index > 0 {
+            tokens.push(Token::Markup(":"));
+          }
+          tokens.extend(tokenize(choice));
+        }
+        tokens.push(Token::Markup(";"));
+        i = choice_end;
+      }
+      None => {
+        tokens.push(Token::Markup(&text[i..end]));
+        i = end;
+      }
+    }
+    start = i;
+  }
+  if start < bytes.len() {
+    tokens.push(Token::Text(&text[start..]));
+  }
+
+  tokens
+}
+
+/// Convert the human texts (include the visible texts of the choices and hyperlinks) and keep the WoW markups.
+pub fn convert_markup(text: &str, convert: impl Fn(&str) -> String) -> String {
+  tokenize(text)
+    .into_iter()
+    .map(|v| match v {
+      Token::Text(text) => convert(text),
+      Token::Markup(markup) => markup.to_string(),
+    })
+    .collect()
+}
+
+#[test]
+fn convert_markup_test() {
+  let convert = |text: &str| convert_markup(text, |v| format!("<{v}>"));
+  assert_eq!(
+    convert("你好，$N。我从来没见过像你这样的$R$C！$B$B你能帮帮我们吗？"),
+    "<你好，>$N<。我从来没见过像你这样的>$R$C<！>$B$B<你能帮帮我们吗？>"
+  );
+  assert_eq!(
+    convert("谢谢你，$g先生:女士;。这是给你的奖励。"),
+    "<谢谢你，>$g<先生>:<女士>;<。这是给你的奖励。>"
+  );
+  assert_eq!(
+    convert("把|cFFFFD100暴风城|r的信交给|Hitem:6948:0:0:0|h[炉石]|h的主人。"),
+    "<把>|cFFFFD100<暴风城>|r<的信交给>|Hitem:6948:0:0:0|h<[炉石]>|h<的主人。>"
+  );
+  assert_eq!(
+    convert("%s的攻击造成了%d点伤害，%%5.1f不是参数。"),
+    "%s<的攻击造成了>%d<点伤害，>%%<5.1f不是参数。>"
+  );
+  assert_eq!(
+    convert("|TInterface\\Icons\\INV_Misc_Coin_01:16|t 100% 的金币|n"),
+    "|TInterface\\Icons\\INV_Misc_Coin_01:16|t< 100% 的金币>|n"
+  );
+  // The incomplete markups are the human texts.
+  assert_eq!(convert("$g先生;|cFFFF 50$"), "$g<先生;|cFFFF 50$>");
+  assert_eq!(convert(""), "");
+}
@@ -0,0 +1,305 @@
+use std::{
+  collections::{hash_map::RandomState, BTreeMap, HashMap},
+  fs,
+  hash::BuildHasher,
+  path::Path,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+  },
+};
+
+use anyhow::Context;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+
+use super::LocalePair;
+
+/// The shards count of the entries of each direction, the convert threads rarely wait for the same shard lock.
+const SHARDS_COUNT: usize = 16;
+/// The max entries count of the memory, the new texts aren't remembered when the shard is full,
+/// so the memory doesn't grow with the table size.
+const MAX_ENTRIES_COUNT: usize = 1 << 18;
+/// The max bytes length of the remembered texts, the long texts (e.g. quest details) rarely repeat.
+const MAX_TEXT_LENGTH: usize = 512;
+
+/// The conversion profile of a direction, it's saved with the entries to check whether they're still valid.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemoryProfile {
+  pub pair: LocalePair,
+  /// The OpenCC config and the conversion logic version, all the entries are invalidated when it changes.
+  pub converter: String,
+  /// The glossary terms (source phrase -> target phrase), only the entries which contain the changed terms are invalidated.
+  pub terms: BTreeMap<String, String>,
+}
+
+impl MemoryProfile {
+  /// The source phrases which are added, removed or changed in this profile since the saved profile.
+  fn changed_terms<'a>(&'a self, saved: &'a Self) -> Vec<&'a str> {
+    let mut phrases: Vec<_> = self
+      .terms
+      .keys()
+      .chain(saved.terms.keys())
+      .filter(|v| self.terms.get(*v) != saved.terms.get(*v))
+      .map(String::as_str)
+      .collect();
+    phrases.sort_unstable();
+    phrases.dedup();
+    phrases
+  }
+}
+
+/// The converted text of the source text in the direction.
+#[derive(Debug, Serialize, Deserialize)]
+struct MemoryEntry {
+  pair: LocalePair,
+  source: String,
+  converted: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MemoryFile {
+  profiles: Vec<MemoryProfile>,
+  entries: Vec<MemoryEntry>,
+}
+
+/// The translation memory of the repeated short texts, the texts are converted once for each direction.
+/// The entries are keyed by the source texts, so a remembered text is never returned for another source text.
+pub struct TranslationMemory {
+  /// The conversion profile of each direction, the texts of the other directions aren't remembered.
+  profiles: Vec<MemoryProfile>,
+  /// The entry shards of each profile, the shards of the profile `i` are `shards[i * SHARDS_COUNT..][..SHARDS_COUNT]`.
+  shards: Vec<Mutex<HashMap<String, String>>>,
+  hasher: RandomState,
+  hits: AtomicU64,
+  misses: AtomicU64,
+}
+
+impl TranslationMemory {
+  pub fn new(profiles: Vec<MemoryProfile>) -> Self {
+    TranslationMemory {
+      shards: (0..profiles.len() * SHARDS_COUNT)
+        .map(|_| Mutex::default())
+        .collect(),
+      profiles,
+      hasher: RandomState::new(),
+      hits: AtomicU64::new(0),
+      misses: AtomicU64::new(0),
+    }
+  }
+
+  fn shard(&self, pair: LocalePair, text: &str) -> Option<&Mutex<HashMap<String, String>>> {
+    let index = self.profiles.iter().position(|v| v.pair == pair)?;
+    let shard_index = self.hasher.hash_one(text) as usize % SHARDS_COUNT;
+    Some(&self.shards[index * SHARDS_COUNT + shard_index])
+  }
+
+  /// Remember the converted text unless the shard is full.
+  fn insert(&self, pair: LocalePair, source: String, converted: String) {
+    let Some(shard) = self.shard(pair, &source) else {
+      return;
+    };
+    let mut shard = shard.lock().unwrap();
+    if shard.len() < MAX_ENTRIES_COUNT / self.shards.len() {
+      shard.insert(source, converted);
+    }
+  }
+
+  fn entries_count(&self) -> usize {
+    self.shards.iter().map(|v| v.lock().unwrap().len()).sum()
+  }
+
+  /// Load the persisted entries, the entries of the changed OpenCC configs and the entries which contain
+  /// the changed glossary terms are dropped. The memory is only a cache, so the invalid file is ignored and rebuilt.
+  pub fn load(&mut self, path: &Path) -> anyhow::Result<()> {
+    if !path.exists() {
+      return Ok(());
+    }
+    let content = fs::read_to_string(path)
+      .with_context(|| format!("Read translation memory file {path:?} failed"))?;
+    let file: MemoryFile = match serde_json::from_str(&content) {
+      Ok(file) => file,
+      Err(e) => {
+        warn!("Parse translation memory file {path:?} failed ({e}), it's rebuilt ...");
+        return Ok(());
+      }
+    };
+
+    // The changed terms of each direction, none of the entries are valid when the converter changes.
+    let changed_terms: Vec<_> = self
+      .profiles
+      .iter()
+      .map(|profile| {
+        let saved = file.profiles.iter().find(|v| v.pair == profile.pair)?;
+        (saved.converter == profile.converter).then(|| (profile.pair, profile.changed_terms(saved)))
+      })
+      .collect();
+    let mut dropped_count = 0;
+    for entry in file.entries {
+      let valid = changed_terms.iter().flatten().any(|(pair, terms)| {
+        *pair == entry.pair && !terms.iter().any(|v| entry.source.contains(v))
+      });
+      if valid {
+        self.insert(entry.pair, entry.source, entry.converted);
+      } else {
+        dropped_count += 1;
+      }
+    }
+    info!(
+      "Load translation memory {path:?} (entries count: {}, invalidated entries count: {dropped_count}) ...",
+      self.entries_count()
+    );
+    Ok(())
+  }
+
+  /// Persist the profiles and the entries, so the next run can reuse the entries which are still valid.
+  /// The file is written to a temporary file first and renamed, so an interrupted save keeps the old file.
+  pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+    let mut entries = vec![];
+    for (i, profile) in self.profiles.iter().enumerate() {
+      for shard in &self.shards[i * SHARDS_COUNT..][..SHARDS_COUNT] {
+        for (source, converted) in shard.lock().unwrap().iter() {
+          entries.push(MemoryEntry {
+            pair: profile.pair,
+            source: source.clone(),
+            converted: converted.clone(),
+          });
+        }
+      }
+    }
+    let file = MemoryFile {
+      profiles: self.profiles.clone(),
+      entries,
+    };
+
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    fs::write(&temp_path, serde_json::to_string(&file)?)
+      .with_context(|| format!("Write translation memory file {temp_path:?} failed"))?;
+    fs::rename(&temp_path, path)
+      .with_context(|| format!("Replace translation memory file {path:?} failed"))?;
+    debug!(
+      "Save translation memory {path:?} (entries count: {})",
+      file.entries.len()
+    );
+    Ok(())
+  }
+
+  /// Get the converted text from the memory, the short text is converted and remembered when it's missing.
+  pub fn convert(
+    &self,
+    pair: LocalePair,
+    text: &str,
+    convert: impl FnOnce(&str) -> String,
+  ) -> String {
+    if text.len() > MAX_TEXT_LENGTH {
+      return convert(text);
+    }
+    let Some(shard) = self.shard(pair, text) else {
+      return convert(text);
+    };
+    if let Some(converted) = shard.lock().unwrap().get(text) {
+      self.hits.fetch_add(1, Ordering::Relaxed);
+      return converted.clone();
+    }
+    self.misses.fetch_add(1, Ordering::Relaxed);
+    // Convert without the lock, the same text converted concurrently just produces the same entry.
+    let converted = convert(text);
+    self.insert(pair, text.to_string(), converted.clone());
+    converted
+  }
+
+  /// Log the hit rate of the run.
+  pub fn report(&self) {
+    let (hits, misses) = (
+      self.hits.load(Ordering::Relaxed),
+      self.misses.load(Ordering::Relaxed),
+    );
+    if hits + misses > 0 {
+      info!(
+        "Translation memory hits: {hits}/{} ({:.1}%), entries count: {}",
+        hits + misses,
+        hits as f64 * 100.0 / (hits + misses) as f64,
+        self.entries_count()
+      );
+    }
+  }
+}
+
+#[test]
+fn translation_memory_test() -> anyhow::Result<()> {
+  let [from_taiwanese, from_chinese] = LocalePair::CHINESE;
+  let path = std::env::temp_dir().join(format!("translation_memory_{}.json", std::process::id()));
+  let profiles = |converter: &str, terms: &[(&str, &str)]| {
+    vec![
+      MemoryProfile {
+        pair: from_taiwanese,
+        converter: "tw2sp".into(),
+        terms: BTreeMap::new(),
+      },
+      MemoryProfile {
+        pair: from_chinese,
+        converter: converter.into(),
+        terms: terms
+          .iter()
+          .map(|(source, target)| (source.to_string(), target.to_string()))
+          .collect(),
+      },
+    ]
+  };
+
+  let memory = TranslationMemory::new(profiles("s2twp", &[("暴风", "風暴")]));
+  assert_eq!(
+    memory.convert(from_chinese, "简体", |_| "簡體".into()),
+    "簡體"
+  );
+  assert_eq!(
+    memory.convert(from_chinese, "简体", |_| unreachable!()),
+    "簡體"
+  );
+  assert_eq!(
+    memory.convert(from_chinese, "暴风城", |_| "風暴城".into()),
+    "風暴城"
+  );
+  assert_eq!(
+    memory.convert(from_taiwanese, "简体", |_| "简体".into()),
+    "简体"
+  );
+  assert_eq!(memory.hits.load(Ordering::Relaxed), 1);
+  memory.save(&path)?;
+
+  // Only the entries which contain the changed glossary terms are invalidated.
+  let mut memory = TranslationMemory::new(profiles("s2twp", &[("暴风", "暴風")]));
+  memory.load(&path)?;
+  assert_eq!(
+    memory.convert(from_chinese, "简体", |_| unreachable!()),
+    "簡體"
+  );
+  assert_eq!(
+    memory.convert(from_chinese, "暴风城", |_| "暴風城".into()),
+    "暴風城"
+  );
+  // All the entries of the changed OpenCC config are invalidated.
+  let mut memory = TranslationMemory::new(profiles("s2tw", &[("暴风", "風暴")]));
+  memory.load(&path)?;
+  assert_eq!(
+    memory.convert(from_chinese, "简体", |_| "简体字".into()),
+    "简体字"
+  );
+  assert_eq!(
+    memory.convert(from_taiwanese, "简体", |_| unreachable!()),
+    "简体"
+  );
+  // The long texts aren't remembered.
+  let long_text = "长".repeat(MAX_TEXT_LENGTH);
+  memory.convert(from_chinese, &long_text, |v| v.to_string());
+  memory.convert(from_chinese, &long_text, |v| v.to_string());
+  assert_eq!(memory.entries_count(), 2);
+
+  // The invalid file is rebuilt.
+  fs::write(&path, "{")?;
+  memory.load(&path)?;
+  fs::remove_file(&path)?;
+
+  Ok(())
+}
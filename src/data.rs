@@ -1,2 +1,469 @@
-pub mod azeroth_core;
-pub mod mangos;
+use std::{
+  collections::{BTreeMap, HashMap},
+  fs,
+  path::PathBuf,
+};
+
+use anyhow::{bail, Context};
+use serde::Deserialize;
+
+use crate::{
+  common::{Language, LocalePair, ServerType, TranslatePolicy},
+  translate::{LocaleSchema, TranslateTarget},
+};
+
+/// The built-in table registry, it's always loaded before the user registry files.
+const DEFAULT_REGISTRY: &str = include_str!("data/registry.toml");
+
+/// The table definition in registry file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TableDefinition {
+  pub database: Option<String>,
+  pub table: String,
+  pub key_columns: Vec<String>,
+  pub locale_column: Option<String>,
+  pub locale_column_pattern: Option<String>,
+  pub text_columns: Vec<String>,
+  #[serde(default)]
+  pub extra_columns: Vec<String>,
+  /// The source of truth locale of the table, default is translating from whichever locale exists.
+  #[serde(default)]
+  pub policy: TranslatePolicy,
+}
+
+impl TableDefinition {
+  fn validate(&self) -> anyhow::Result<()> {
+    let table = &self.table;
+    if self.key_columns.is_empty() {
+      bail!("Table {table} doesn't define any key column!");
+    }
+    if self.text_columns.is_empty() {
+      bail!("Table {table} doesn't define any text column!");
+    }
+    match (&self.locale_column, &self.locale_column_pattern) {
+      (Some(_), None) => {}
+      (None, Some(pattern)) if pattern.contains("{column}") && pattern.contains("{index}") => {}
+      (None, Some(pattern)) => {
+        bail!("Table {table} locale column pattern \"{pattern}\" must contain {{column}} and {{index}}!")
+      }
+      _ => {
+        bail!("Table {table} must define exactly one of locale_column and locale_column_pattern!")
+      }
+    }
+    Ok(())
+  }
+
+  pub fn to_target(&self, server_type: &ServerType) -> TranslateTarget {
+    let locale = match (&self.locale_column, &self.locale_column_pattern) {
+      (Some(column), _) => LocaleSchema::Row(column.clone()),
+      (_, Some(pattern)) => LocaleSchema::Column(pattern.clone()),
+      _ => unreachable!("Table definitions are validated when the registry is loaded"),
+    };
+    TranslateTarget {
+      database: self.database.clone().unwrap_or(server_type.to_string()),
+      table: self.table.clone(),
+      key_columns: self.key_columns.clone(),
+      locale,
+      text_columns: self.text_columns.clone(),
+      extra_columns: self.extra_columns.clone(),
+      policy: self.policy,
+      filter: vec![],
+    }
+  }
+}
+
+/// The translate table registry, table definitions are grouped by server family.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Registry {
+  #[serde(default)]
+  pub azeroth_core: Vec<TableDefinition>,
+  #[serde(default)]
+  pub mangos: Vec<TableDefinition>,
+}
+
+impl Registry {
+  /// Load the built-in registry and merge the user registry files into it.
+  pub fn load(paths: &[PathBuf]) -> anyhow::Result<Self> {
+    let mut registry = Self::parse(DEFAULT_REGISTRY).context("Parse built-in registry failed")?;
+    for path in paths {
+      let content =
+        fs::read_to_string(path).with_context(|| format!("Read registry file {path:?} failed"))?;
+      let user_registry =
+        Self::parse(&content).with_context(|| format!("Parse registry file {path:?} failed"))?;
+      registry.merge(user_registry);
+    }
+    Ok(registry)
+  }
+
+  fn parse(content: &str) -> anyhow::Result<Self> {
+    let registry: Self = toml::from_str(content)?;
+    for definition in registry.azeroth_core.iter().chain(&registry.mangos) {
+      definition.validate()?;
+    }
+    Ok(registry)
+  }
+
+  /// Table definitions with the same name replace the existing ones, others are appended.
+  fn merge(&mut self, other: Self) {
+    fn merge_definitions(definitions: &mut Vec<TableDefinition>, others: Vec<TableDefinition>) {
+      for other in others {
+        match definitions.iter_mut().find(|v| v.table == other.table) {
+          Some(definition) => *definition = other,
+          None => definitions.push(other),
+        }
+      }
+    }
+    merge_definitions(&mut self.azeroth_core, other.azeroth_core);
+    merge_definitions(&mut self.mangos, other.mangos);
+  }
+
+  /// Get the translate targets of the server type which match the table patterns and don't match the exclude patterns,
+  /// all the targets are selected when there is no table pattern. Each pattern must match at least one table.
+  pub fn select_targets(
+    &self,
+    server_type: &ServerType,
+    tables: &[String],
+    exclude_tables: &[String],
+  ) -> anyhow::Result<Vec<TranslateTarget>> {
+    let targets = self.targets(server_type);
+    for pattern in tables.iter().chain(exclude_tables) {
+      if !targets.iter().any(|v| glob_match(pattern, &v.table)) {
+        let names: Vec<_> = targets.iter().map(|v| v.table.as_str()).collect();
+        bail!("Table pattern \"{pattern}\" doesn't match any {server_type} table, the valid tables: {names:?}");
+      }
+    }
+
+    Ok(
+      targets
+        .into_iter()
+        .filter(|v| tables.is_empty() || tables.iter().any(|p| glob_match(p, &v.table)))
+        .filter(|v| !exclude_tables.iter().any(|p| glob_match(p, &v.table)))
+        .collect(),
+    )
+  }
+
+  /// Get the translate targets of the server type.
+  pub fn targets(&self, server_type: &ServerType) -> Vec<TranslateTarget> {
+    let definitions = match server_type {
+      ServerType::AzerothCore => &self.azeroth_core,
+      ServerType::Mangos0 | ServerType::Mangos1 | ServerType::Mangos2 => &self.mangos,
+    };
+    definitions
+      .iter()
+      .map(|v| v.to_target(server_type))
+      .collect()
+  }
+}
+
+/// Match the name with the glob pattern, `*` matches any characters and `?` matches one character.
+fn glob_match(pattern: &str, name: &str) -> bool {
+  let (pattern, name): (Vec<_>, Vec<_>) = (pattern.chars().collect(), name.chars().collect());
+  let (mut p, mut n) = (0, 0);
+  // The position of the last `*` in the pattern and the name position it matches from.
+  let mut star = None;
+  while n < name.len() {
+    if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+      p += 1;
+      n += 1;
+    } else if p < pattern.len() && pattern[p] == '*' {
+      star = Some((p, n));
+      p += 1;
+    } else if let Some((star_p, star_n)) = star {
+      // Let the last `*` match one more character.
+      p = star_p + 1;
+      n = star_n + 1;
+      star = Some((star_p, star_n + 1));
+    } else {
+      return false;
+    }
+  }
+  pattern[p..].iter().all(|&v| v == '*')
+}
+
+/// The glossary definition in glossary file, the phrases of the source locale are replaced by the target locale phrases.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GlossaryDefinition {
+  source: Language,
+  target: Language,
+  #[serde(default)]
+  terms: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct GlossaryFile {
+  #[serde(default)]
+  glossary: Vec<GlossaryDefinition>,
+}
+
+/// The glossary terms of one translation direction.
+#[derive(Debug)]
+struct GlossaryTerms {
+  pair: LocalePair,
+  phrases: HashMap<String, String>,
+  /// The chars count of the longest source phrase, it limits the phrase matching.
+  max_chars: usize,
+}
+
+/// The user maintained glossary, the matched phrases take priority over the OpenCC conversion.
+#[derive(Debug, Default)]
+pub struct Glossary {
+  terms: Vec<GlossaryTerms>,
+}
+
+impl Glossary {
+  /// Load the glossary files, the terms in the later files replace the same source phrases.
+  pub fn load(paths: &[PathBuf]) -> anyhow::Result<Self> {
+    let mut glossary = Self::default();
+    for path in paths {
+      let content =
+        fs::read_to_string(path).with_context(|| format!("Read glossary file {path:?} failed"))?;
+      glossary
+        .parse(&content)
+        .with_context(|| format!("Parse glossary file {path:?} failed"))?;
+    }
+    Ok(glossary)
+  }
+
+  fn parse(&mut self, content: &str) -> anyhow::Result<()> {
+    let file: GlossaryFile = toml::from_str(content)?;
+    for definition in file.glossary {
+      let pair = LocalePair::new(definition.source, definition.target)?;
+      if definition.terms.contains_key("") {
+        bail!("The glossary {pair} contains the empty source phrase!");
+      }
+      let index = match self.terms.iter().position(|v| v.pair == pair) {
+        Some(index) => index,
+        None => {
+          self.terms.push(GlossaryTerms {
+            pair,
+            phrases: HashMap::new(),
+            max_chars: 0,
+          });
+          self.terms.len() - 1
+        }
+      };
+      let terms = &mut self.terms[index];
+      for (source, target) in definition.terms {
+        terms.max_chars = terms.max_chars.max(source.chars().count());
+        terms.phrases.insert(source, target);
+      }
+    }
+    Ok(())
+  }
+
+  /// The glossary terms of the locale pair (source phrase -> target phrase) in the phrase order.
+  pub fn phrases(&self, pair: LocalePair) -> BTreeMap<String, String> {
+    self
+      .terms
+      .iter()
+      .find(|v| v.pair == pair)
+      .map(|v| v.phrases.clone().into_iter().collect())
+      .unwrap_or_default()
+  }
+
+  /// Convert the text by the locale pair, the longest glossary phrase is matched first at each position,
+  /// and the texts between the matched phrases are converted by the fallback conversion (OpenCC).
+  pub fn convert(&self, pair: LocalePair, text: &str, convert: impl Fn(&str) -> String) -> String {
+    let Some(terms) = self.terms.iter().find(|v| v.pair == pair) else {
+      return convert(text);
+    };
+    let boundaries: Vec<_> = text
+      .char_indices()
+      .map(|(i, _)| i)
+      .chain([text.len()])
+      .collect();
+    let chars_count = boundaries.len() - 1;
+    let mut result = String::with_capacity(text.len());
+    // The start of the text which isn't matched by the glossary.
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < chars_count {
+      let matched = (1..=terms.max_chars.min(chars_count - i))
+        .rev()
+        .find_map(|len| {
+          let phrase = terms
+            .phrases
+            .get(&text[boundaries[i]..boundaries[i + len]])?;
+          Some((len, phrase))
+        });
+      match matched {
+        Some((len, phrase)) => {
+          if start < boundaries[i] {
+            result.push_str(&convert(&text[start..boundaries[i]]));
+          }
+          result.push_str(phrase);
+          i += len;
+          start = boundaries[i];
+        }
+        None => i += 1,
+      }
+    }
+    if start < text.len() {
+      result.push_str(&convert(&text[start..]));
+    }
+
+    result
+  }
+}
+
+#[test]
+fn default_registry_test() -> anyhow::Result<()> {
+  use crate::common::Language;
+
+  let registry = Registry::load(&[])?;
+  let azeroth_core_targets = registry.targets(&ServerType::AzerothCore);
+  let mangos_targets = registry.targets(&ServerType::Mangos1);
+  assert_eq!(azeroth_core_targets.len(), 15);
+  assert_eq!(mangos_targets.len(), 7);
+  assert!(azeroth_core_targets
+    .iter()
+    .all(|v| v.database == "acore_world" && matches!(v.locale, LocaleSchema::Row(_))));
+  assert!(mangos_targets
+    .iter()
+    .all(|v| v.database == "mangos1" && matches!(v.locale, LocaleSchema::Column(_))));
+  // The locale tables only have the columns of loc1 ~ loc8, the enUS texts are in the base tables.
+  let columns: Vec<_> = [Language::English, Language::Korean, Language::Russian]
+    .into_iter()
+    .map(|v| mangos_targets[0].locale_column("name", v))
+    .collect();
+  assert_eq!(
+    columns,
+    [None, Some("name_loc1".into()), Some("name_loc8".into())]
+  );
+
+  let mut registry = registry;
+  registry.merge(Registry::parse(
+    r#"
+    [[azeroth_core]]
+    table = "quest_template_locale"
+    key_columns = ["ID"]
+    locale_column = "locale"
+    text_columns = ["Title"]
+
+    [[azeroth_core]]
+    database = "acore_custom"
+    table = "custom_text_locale"
+    policy = "zhCN"
+    key_columns = ["ID"]
+    locale_column = "Locale"
+    text_columns = ["Text"]
+    "#,
+  )?);
+  let azeroth_core_targets = registry.targets(&ServerType::AzerothCore);
+  assert_eq!(azeroth_core_targets.len(), 16);
+  assert!(azeroth_core_targets
+    .iter()
+    .any(|v| v.table == "quest_template_locale" && v.text_columns == ["Title"]));
+  assert!(azeroth_core_targets
+    .iter()
+    .any(|v| v.database == "acore_custom"
+      && v.table == "custom_text_locale"
+      && v.policy == TranslatePolicy::FromChinese));
+
+  assert!(Registry::parse(
+    r#"
+    [[mangos]]
+    table = "locales_broken"
+    key_columns = ["entry"]
+    locale_column_pattern = "{column}_loc"
+    text_columns = ["name"]
+    "#
+  )
+  .is_err());
+
+  Ok(())
+}
+
+#[test]
+fn select_targets_test() -> anyhow::Result<()> {
+  assert!(glob_match("quest_*", "quest_template_locale"));
+  assert!(glob_match("*_locale", "quest_template_locale"));
+  assert!(glob_match("quest_?emplate*", "quest_template_locale"));
+  assert!(!glob_match("quest_*", "locales_quest"));
+  assert!(!glob_match("quest", "quest_template_locale"));
+
+  let registry = Registry::load(&[])?;
+  let server_type = ServerType::AzerothCore;
+  let targets = registry.select_targets(&server_type, &["quest_*".into()], &[])?;
+  assert!(!targets.is_empty());
+  assert!(targets.iter().all(|v| v.table.starts_with("quest_")));
+  let targets = registry.select_targets(
+    &server_type,
+    &[],
+    &["quest_*".into(), "item_template_locale".into()],
+  )?;
+  assert_eq!(
+    targets.len(),
+    registry.targets(&server_type).len()
+      - registry
+        .select_targets(&server_type, &["quest_*".into()], &[])?
+        .len()
+      - 1
+  );
+  let error = registry
+    .select_targets(&server_type, &["unknown_*".into()], &[])
+    .unwrap_err();
+  assert!(error.to_string().contains("quest_template_locale"));
+
+  Ok(())
+}
+
+#[test]
+fn glossary_test() -> anyhow::Result<()> {
+  let mut glossary = Glossary::default();
+  glossary.parse(
+    r#"
+[[glossary]]
+source = "zhCN"
+target = "zhTW"
+terms = { "暴风" = "風暴", "暴风城" = "暴風城" }
+"#,
+  )?;
+  let [from_taiwanese, from_chinese] = LocalePair::CHINESE;
+  let convert = |v: &str| format!("[{v}]");
+  assert_eq!(
+    glossary.convert(from_chinese, "进入暴风城和暴风", convert),
+    "[进入]暴風城[和]風暴"
+  );
+  assert_eq!(glossary.convert(from_chinese, "暴风城", convert), "暴風城");
+  assert_eq!(glossary.convert(from_chinese, "", convert), "");
+  assert_eq!(
+    glossary.convert(from_taiwanese, "暴风城", convert),
+    "[暴风城]"
+  );
+  assert!(glossary.phrases(from_taiwanese).is_empty());
+
+  // The later terms replace the same source phrases.
+  glossary.parse(
+    r#"
+[[glossary]]
+source = "zhCN"
+target = "zhTW"
+terms = { "暴风" = "暴風" }
+"#,
+  )?;
+  assert_eq!(
+    glossary.convert(from_chinese, "暴风雪", convert),
+    "暴風[雪]"
+  );
+  assert_eq!(
+    glossary
+      .phrases(from_chinese)
+      .into_iter()
+      .collect::<Vec<_>>(),
+    [
+      ("暴风".to_string(), "暴風".to_string()),
+      ("暴风城".to_string(), "暴風城".to_string())
+    ]
+  );
+  assert!(glossary
+    .parse("[[glossary]]\nsource = \"zhCN\"\ntarget = \"enUS\"")
+    .is_err());
+
+  Ok(())
+}
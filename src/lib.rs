@@ -0,0 +1,3 @@
+pub mod common;
+pub mod data;
+pub mod translate;
@@ -1,27 +1,30 @@
-mod common;
-mod data;
-mod translate;
-
 use clap::CommandFactory;
-use common::*;
-use translate::*;
+use wow_database_translator::{common::*, translate::*};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
   init_logger();
+  init_glossary()?;
+  init_memory()?;
 
-  if let Some(ServerType::AzerothCore) = &COMMAND_LINE.translate {
-    azeroth_core::translate_tables().await?;
-  } else if let Some(v) = &COMMAND_LINE.translate {
-    mangos::translate_tables(v).await?;
-  } else if let Some(ServerType::AzerothCore) = &COMMAND_LINE.check {
-    azeroth_core::check_translations().await?;
+  if let Some(v) = &COMMAND_LINE.translate {
+    translate_tables(v).await?;
+  } else if let Some(v) = &COMMAND_LINE.plan {
+    plan::write_plan(v).await?;
+  } else if COMMAND_LINE.apply {
+    plan::apply_plan().await?;
   } else if let Some(v) = &COMMAND_LINE.check {
-    mangos::check_translations(v).await?;
+    if check_translations(v).await?.has_untranslated() {
+      // Exit with the distinct code, so the pipelines can gate on the untranslated contents.
+      std::process::exit(report::UNTRANSLATED_EXIT_CODE);
+    }
+  } else if let Some(v) = &COMMAND_LINE.diff {
+    diff::diff_tables(v).await?;
   } else {
     // Print help message when there is no action command input.
     CommandLine::command().print_long_help()?;
   }
+  save_memory()?;
 
   Ok(())
 }
@@ -1,241 +1,534 @@
-use log::{debug, info};
-use sqlx::{mysql::MySqlRow, MySql, Row};
-use tokio::{task::JoinSet, try_join};
+use sqlx::{mysql::MySqlConnection, MySql, QueryBuilder, Row};
 
-use crate::{
-  common::{Language, COMMAND_LINE, POOL},
-  data::azeroth_core::*,
-};
+use crate::common::{Language, LocalePair, COMMAND_LINE, POOL};
 
-use super::{TranslateLogic, TranslateTarget};
+use super::{
+  build_insert_query, build_upsert_query, literal_tuple, push_tuple, push_tuples, quote,
+  report::LocaleCoverage, LocaleSchema, TranslateBackend, TranslateRow, TranslateTarget,
+  TranslateTask, Value, MAX_BIND_PARAMETERS,
+};
 
-macro_rules! translate_tables {
-  ($($data_type: ty),*) => {
-    $(translate_table::<$data_type>().await?;)*
-  };
-  (+$($data_type: ty),*) => {
-    try_join!($(translate_table::<$data_type>(),)*)?
-  };
+/// The translate backend of the tables which store one row per locale (AzerothCore style),
+/// the untranslated rows are inserted as new rows of the target locale,
+/// and the empty text columns of the existing target locale rows are filled column by column.
+pub struct RowPerLocale<'a> {
+  target: &'a TranslateTarget,
+  locale_column: String,
 }
 
-/// Table translate logic.
-pub async fn translate_tables() -> anyhow::Result<()> {
-  info!("Run table translate ...");
-
-  if COMMAND_LINE.r#async {
-    translate_tables!(
-      +
-      AchievementRewardLocale,
-      BroadcastTextLocale,
-      CreatureTemplateLocale,
-      CreatureTextLocale,
-      GameobjectTemplateLocale,
-      GossipMenuOptionLocale,
-      ItemSetNamesLocale,
-      ItemTemplateLocale,
-      NpcTextLocale,
-      PageTextLocale,
-      PointsOfInterestLocale,
-      QuestGreetingLocale,
-      QuestOfferRewardLocale,
-      QuestRequestItemsLocale,
-      QuestTemplateLocale
-    );
-    // Macro expanded:
-    // try_join!(
-    //   translate_table::<AchievementRewardLocale>(),
-    //   translate_table::<BroadcastTextLocale>(),
-    //   ...
-    // )?;
-  } else {
-    translate_tables!(
-      AchievementRewardLocale,
-      BroadcastTextLocale,
-      CreatureTemplateLocale,
-      CreatureTextLocale,
-      GameobjectTemplateLocale,
-      GossipMenuOptionLocale,
-      ItemSetNamesLocale,
-      ItemTemplateLocale,
-      NpcTextLocale,
-      PageTextLocale,
-      PointsOfInterestLocale,
-      QuestGreetingLocale,
-      QuestOfferRewardLocale,
-      QuestRequestItemsLocale,
-      QuestTemplateLocale
-    );
+impl<'a> RowPerLocale<'a> {
+  pub fn new(target: &'a TranslateTarget) -> Self {
+    let LocaleSchema::Row(locale_column) = &target.locale else {
+      unreachable!(
+        "Table {}.{} doesn't store one row per locale",
+        target.database, target.table
+      )
+    };
+    RowPerLocale {
+      target,
+      locale_column: quote(locale_column),
+    }
   }
 
-  Ok(())
-}
+  /// Build the query of the locale rows, the columns are selected from the table aliased as `origin`.
+  fn locale_query(&self, columns: &str, language: Language) -> QueryBuilder<'static, MySql> {
+    let TranslateTarget {
+      database, table, ..
+    } = self.target;
+    let mut builder = QueryBuilder::new(format!(
+      "SELECT {columns} FROM {}.{} AS `origin` WHERE {} = ",
+      quote(database),
+      quote(table),
+      self.locale_column,
+    ));
+    builder.push_bind(language);
+    builder
+  }
+
+  /// Build the query of the origin locale rows whose keys don't exist in the target locale (an anti-join on the key columns),
+  /// all the origin locale rows are selected in overwrite mode.
+  /// In fill mode the origin locale rows which have the text but the target locale rows exist with the empty text are selected.
+  fn untranslated_query(
+    &self,
+    columns: &str,
+    task: &TranslateTask,
+  ) -> QueryBuilder<'static, MySql> {
+    let mut builder = self.locale_query(columns, task.pair.source());
+    self.target.push_filter(&mut builder, Some("origin"));
+    if task.overwrite {
+      return builder;
+    }
 
-async fn check_translation<T: TranslateLogic>() -> anyhow::Result<(bool, String, (i64, i64))> {
-  let TranslateTarget {
-    database, table, ..
-  } = &*T::TARGET;
+    let TranslateTarget {
+      database,
+      table,
+      key_columns,
+      ..
+    } = self.target;
+    let (database, table) = (quote(database), quote(table));
+    let same_keys = key_columns
+      .iter()
+      .map(|v| format!("`translated`.{0} = `origin`.{0}", quote(v)))
+      .collect::<Vec<_>>()
+      .join(" AND ");
+    if task.fill {
+      let text_column = quote(&task.text_columns[0]);
+      builder.push(format!(
+        " AND `origin`.{text_column} IS NOT NULL AND `origin`.{text_column} != '' AND EXISTS (SELECT 1 FROM {database}.{table} AS `translated` WHERE {same_keys} AND (`translated`.{text_column} IS NULL OR `translated`.{text_column} = '') AND `translated`.{} = ",
+        self.locale_column
+      ));
+    } else {
+      builder.push(format!(
+        " AND NOT EXISTS (SELECT 1 FROM {database}.{table} AS `translated` WHERE {same_keys} AND `translated`.{} = ",
+        self.locale_column
+      ));
+    }
+    builder.push_bind(task.pair.target());
+    builder.push(")");
 
-  let taiwanese_count = data_count::<T>(Language::Taiwanese).await?;
-  let chinese_count = data_count::<T>(Language::Chinese).await?;
-  info!(
-    "Table {database}.{table} is equal: {} (taiwanese count: {taiwanese_count}, chinese count: {chinese_count}) ... ",
-    taiwanese_count == chinese_count
-  );
+    builder
+  }
 
-  Ok((
-    taiwanese_count == chinese_count,
-    table.clone(),
-    (taiwanese_count, chinese_count),
-  ))
-}
+  /// Build the multi-row update SQL of the filled text column:
+  /// UPDATE ... SET column = CASE WHEN (keys) = (...) THEN ... END WHERE locale = ... AND (keys) IN ((...), ...)
+  fn build_fill_query(
+    &self,
+    task: &TranslateTask,
+    rows: &[TranslateRow],
+  ) -> QueryBuilder<'static, MySql> {
+    let TranslateTarget {
+      database, table, ..
+    } = self.target;
+    let text_column = quote(&task.text_columns[0]);
+    let keys = self.target.key_tuple();
 
-macro_rules! check_translations {
-  (+ $join_set: ident, $($data_type: ty),*) => {
-    $($join_set.spawn(check_translation::<$data_type>());)*
-  };
-  ($($data_type: ty),*) => {
-    vec![$(check_translation::<$data_type>().await?,)*]
-  };
+    let mut builder = QueryBuilder::new(format!(
+      "UPDATE {}.{} SET {text_column} = CASE",
+      quote(database),
+      quote(table)
+    ));
+    for row in rows {
+      builder.push(format!(" WHEN {keys} = "));
+      push_tuple(&mut builder, &row.keys);
+      builder.push(" THEN ").push_bind(row.texts[0].clone());
+    }
+    builder.push(format!(" END WHERE {} = ", self.locale_column));
+    builder.push_bind(task.pair.target());
+    builder.push(format!(" AND {keys} IN "));
+    push_tuples(&mut builder, rows.iter().map(|v| v.keys.as_slice()));
+    self.target.push_filter(&mut builder, None);
+
+    builder
+  }
+
+  async fn fetch_locale_rows(
+    &self,
+    task: &TranslateTask,
+    language: Language,
+    mut builder: QueryBuilder<'static, MySql>,
+  ) -> anyhow::Result<Vec<TranslateRow>> {
+    builder
+      .build()
+      .fetch_all(&*POOL)
+      .await?
+      .iter()
+      .map(|v| {
+        TranslateRow::decode(
+          v,
+          language,
+          &self.target.key_columns,
+          &task.text_columns,
+          &self.target.extra_columns,
+        )
+      })
+      .collect()
+  }
 }
 
-/// Table translation check logic.
-pub async fn check_translations() -> anyhow::Result<()> {
-  info!("Check table translations ...");
-
-  let task_results = if COMMAND_LINE.r#async {
-    let mut join_set = JoinSet::new();
-
-    check_translations!(
-      + join_set,
-      AchievementRewardLocale,
-      BroadcastTextLocale,
-      CreatureTemplateLocale,
-      CreatureTextLocale,
-      GameobjectTemplateLocale,
-      GossipMenuOptionLocale,
-      ItemSetNamesLocale,
-      ItemTemplateLocale,
-      NpcTextLocale,
-      PageTextLocale,
-      PointsOfInterestLocale,
-      QuestGreetingLocale,
-      QuestOfferRewardLocale,
-      QuestRequestItemsLocale,
-      QuestTemplateLocale
-    );
-
-    let mut results = vec![];
-    while let Some(result) = join_set.join_next().await {
-      results.push(result??);
+impl TranslateBackend for RowPerLocale<'_> {
+  fn target(&self) -> &TranslateTarget {
+    self.target
+  }
+
+  async fn count_untranslated(
+    &self,
+    pair: LocalePair,
+    overwrite: bool,
+  ) -> anyhow::Result<Vec<TranslateTask>> {
+    let mut task = TranslateTask {
+      pair,
+      text_columns: self.target.text_columns.clone(),
+      count: 0,
+      column_counts: vec![],
+      overwrite,
+      fill: false,
+    };
+    // Count the origin locale keys which are missing in the target locale, equal locale counts can still hide different keys.
+    task.count = self
+      .untranslated_query("count(*)", &task)
+      .build()
+      .fetch_one(&*POOL)
+      .await?
+      .get("count(*)");
+    // The text columns of the untranslated rows are inserted together, so they have the same count.
+    task.column_counts = vec![task.count; task.text_columns.len()];
+
+    let mut tasks = vec![];
+    if task.count > 0 {
+      tasks.push(task.clone());
     }
 
-    results
-  } else {
-    check_translations!(
-      AchievementRewardLocale,
-      BroadcastTextLocale,
-      CreatureTemplateLocale,
-      CreatureTextLocale,
-      GameobjectTemplateLocale,
-      GossipMenuOptionLocale,
-      ItemSetNamesLocale,
-      ItemTemplateLocale,
-      NpcTextLocale,
-      PageTextLocale,
-      PointsOfInterestLocale,
-      QuestGreetingLocale,
-      QuestOfferRewardLocale,
-      QuestRequestItemsLocale,
-      QuestTemplateLocale
+    // Count the existing target locale rows which have the empty text column, the columns are filled separately.
+    if !overwrite {
+      for text_column in &self.target.text_columns {
+        let mut task = TranslateTask {
+          text_columns: vec![text_column.clone()],
+          fill: true,
+          ..task.clone()
+        };
+        task.count = self
+          .untranslated_query("count(*)", &task)
+          .build()
+          .fetch_one(&*POOL)
+          .await?
+          .get("count(*)");
+        task.column_counts = vec![task.count];
+        if task.count > 0 {
+          tasks.push(task);
+        }
+      }
+    }
+
+    Ok(tasks)
+  }
+
+  async fn count_locales(&self) -> anyhow::Result<Vec<LocaleCoverage>> {
+    let TranslateTarget {
+      database, table, ..
+    } = self.target;
+    let mut builder = QueryBuilder::new(format!(
+      "SELECT {} AS `locale`, count(*) FROM {}.{} WHERE 1",
+      self.locale_column,
+      quote(database),
+      quote(table)
+    ));
+    self.target.push_filter(&mut builder, None);
+    builder.push(format!(" GROUP BY {0} ORDER BY {0}", self.locale_column));
+    let rows = builder.build().fetch_all(&*POOL).await?;
+
+    // The unknown locales are ignored, the enUS texts are stored in the base table instead of the locale table.
+    Ok(
+      rows
+        .iter()
+        .filter_map(|v| {
+          let locale = Language::try_from(v.get::<String, _>("locale")).ok()?;
+          Some(LocaleCoverage {
+            locale,
+            rows: v.get("count(*)"),
+          })
+        })
+        .collect(),
     )
-  };
+  }
 
-  let not_equal_tables: Vec<_> = task_results
-    .into_iter()
-    .filter(|(v, _, _)| *v == false)
-    .map(|(_, t, _)| t)
-    .collect();
-  if not_equal_tables.is_empty() {
-    info!("All tables' translation count are equal.");
-  } else {
-    info!("Some tables' translation count aren't equal: {not_equal_tables:?}.");
+  async fn fetch_batch(
+    &self,
+    task: &TranslateTask,
+    after: Option<&[Value]>,
+    limit: usize,
+  ) -> anyhow::Result<Vec<TranslateRow>> {
+    let mut builder = self.untranslated_query(&self.target.select_columns(), task);
+    self.target.push_keyset(&mut builder, after);
+    builder.push(format!(
+      " ORDER BY {} LIMIT {}",
+      self.target.key_order(),
+      limit
+    ));
+    self
+      .fetch_locale_rows(task, task.pair.source(), builder)
+      .await
   }
 
-  Ok(())
-}
+  async fn fetch_rows(
+    &self,
+    task: &TranslateTask,
+    keys: &[Vec<Value>],
+  ) -> anyhow::Result<Vec<TranslateRow>> {
+    let mut builder = self.untranslated_query(&self.target.select_columns(), task);
+    builder.push(format!(" AND {} IN ", self.target.key_tuple()));
+    push_tuples(&mut builder, keys.iter().map(Vec::as_slice));
+    self
+      .fetch_locale_rows(task, task.pair.source(), builder)
+      .await
+  }
 
-async fn data_count<T: TranslateLogic>(origin_language: Language) -> anyhow::Result<i64> {
-  let TranslateTarget {
-    database,
-    table,
-    locale_columns,
-  } = &*T::TARGET;
-
-  let locale_column = &locale_columns[0];
-  let count: i64 = sqlx::query::<MySql>(&format!(
-    "SELECT count(*) FROM {database}.{table} WHERE {locale_column} = '{origin_language}'"
-  ))
-  .fetch_one(&*POOL)
-  .await?
-  .get("count(*)");
-
-  Ok(count)
-}
+  // Join the zhTW rows to the zhCN rows, the table aliases are the locale names.
+  async fn fetch_pairs(
+    &self,
+    text_columns: &[String],
+    after: Option<&[Value]>,
+  ) -> anyhow::Result<Vec<(TranslateRow, TranslateRow)>> {
+    let TranslateTarget {
+      database,
+      table,
+      key_columns,
+      ..
+    } = self.target;
+    let (database, table) = (quote(database), quote(table));
+    let languages = [Language::Chinese, Language::Taiwanese];
+    let [chinese, taiwanese] = languages.map(|v| quote(&v.to_string()));
+    let text_aliases = |language: Language| -> Vec<_> {
+      text_columns
+        .iter()
+        .map(|v| format!("{language}_{v}"))
+        .collect()
+    };
 
-async fn translate_language<
-  T: for<'r> sqlx::FromRow<'r, MySqlRow> + Send + Unpin + TranslateLogic,
->(
-  origin_language: Language,
-  origin_count: i64,
-) -> anyhow::Result<()> {
-  let TranslateTarget {
-    database,
-    table,
-    locale_columns,
-  } = &*T::TARGET;
-
-  info!(
-    "Translating table {database}.{table} from {origin_language} (total count: {origin_count}) ..."
-  );
+    let keys = key_columns
+      .iter()
+      .map(|v| format!("{chinese}.{}", quote(v)))
+      .collect::<Vec<_>>()
+      .join(", ");
+    let same_keys = key_columns
+      .iter()
+      .map(|v| format!("{taiwanese}.{0} = {chinese}.{0}", quote(v)))
+      .collect::<Vec<_>>()
+      .join(" AND ");
+    let texts = languages
+      .iter()
+      .flat_map(|&language| {
+        text_columns
+          .iter()
+          .zip(text_aliases(language))
+          .map(move |(column, alias)| {
+            format!(
+              "{}.{} AS {}",
+              quote(&language.to_string()),
+              quote(column),
+              quote(&alias)
+            )
+          })
+      })
+      .collect::<Vec<_>>()
+      .join(", ");
 
-  let locale_column = &locale_columns[0];
-  let mut translate_rows_count = 0;
-  for i in (0..origin_count).step_by(COMMAND_LINE.batch_size) {
-    let results = sqlx::query_as::<MySql, T>(&format!(
-      "SELECT * FROM {database}.{table} WHERE {locale_column} = '{origin_language}' LIMIT {i}, {}",
+    let mut builder = QueryBuilder::new(format!(
+      "SELECT {keys}, {texts} FROM {database}.{table} AS {chinese} JOIN {database}.{table} AS {taiwanese} ON {same_keys} AND {taiwanese}.{} = ",
+      self.locale_column
+    ));
+    builder.push_bind(Language::Taiwanese);
+    builder.push(format!(" WHERE {chinese}.{} = ", self.locale_column));
+    builder.push_bind(Language::Chinese);
+    // The row filters are applied to the zhCN rows, the keys of both rows are the same.
+    self
+      .target
+      .push_filter(&mut builder, Some(&Language::Chinese.to_string()));
+    if let Some(after) = after {
+      builder.push(format!(" AND ({keys}) > "));
+      push_tuple(&mut builder, after);
+    }
+    builder.push(format!(
+      " ORDER BY {keys} LIMIT {}",
       COMMAND_LINE.batch_size
-    ))
-    .fetch_all(&*POOL)
-    .await?;
-
-    let mut insert_results = vec![];
-    for v in results {
-      // Execute the insert SQL.
-      let rows_affected = v
-        .build_query()
+    ));
+
+    builder
+      .build()
+      .fetch_all(&*POOL)
+      .await?
+      .iter()
+      .map(|v| {
+        let [chinese_row, taiwanese_row] = languages.map(|language| {
+          TranslateRow::decode(v, language, key_columns, &text_aliases(language), &[])
+        });
+        Ok((chinese_row?, taiwanese_row?))
+      })
+      .collect()
+  }
+
+  async fn fetch_translated(
+    &self,
+    task: &TranslateTask,
+    keys: &[Vec<Value>],
+  ) -> anyhow::Result<Vec<TranslateRow>> {
+    let mut builder = self.locale_query(&self.target.select_columns(), task.pair.target());
+    builder.push(format!(" AND {} IN ", self.target.key_tuple()));
+    push_tuples(&mut builder, keys.iter().map(Vec::as_slice));
+    self
+      .fetch_locale_rows(task, task.pair.target(), builder)
+      .await
+  }
+
+  async fn write_batch(
+    &self,
+    connection: &mut MySqlConnection,
+    task: &TranslateTask,
+    rows: Vec<TranslateRow>,
+  ) -> anyhow::Result<u64> {
+    let TranslateTarget {
+      key_columns,
+      text_columns,
+      extra_columns,
+      ..
+    } = self.target;
+    let mut rows_affected = 0;
+    if task.fill {
+      let row_parameters = key_columns.len() * 2 + 1;
+      let free_parameters = MAX_BIND_PARAMETERS - 1 - self.target.filter_parameters();
+      for rows in rows.chunks(free_parameters / row_parameters) {
+        rows_affected += self
+          .build_fill_query(task, rows)
+          .build()
+          .execute(&mut *connection)
+          .await?
+          .rows_affected();
+      }
+      return Ok(rows_affected);
+    }
+
+    let row_parameters = key_columns.len() + 1 + text_columns.len() + extra_columns.len();
+    for rows in rows.chunks(MAX_BIND_PARAMETERS / row_parameters) {
+      // Execute the multi-row insert SQL, the existing rows are updated in overwrite mode.
+      let mut builder = if task.overwrite {
+        build_upsert_query(self.target, rows)
+      } else {
+        build_insert_query(self.target, rows)
+      };
+      rows_affected += builder
         .build()
-        .execute(&*POOL)
+        .execute(&mut *connection)
         .await?
         .rows_affected();
-      insert_results.push(rows_affected);
-      translate_rows_count += rows_affected;
     }
+    Ok(rows_affected)
+  }
+
+  // Delete the target locale row before inserting it, so the statements can be applied again.
+  // The filled text column is updated in the existing target locale row.
+  fn export_statements(&self, task: &TranslateTask, rows: &[TranslateRow]) -> Vec<String> {
+    let TranslateTarget {
+      database,
+      table,
+      key_columns,
+      text_columns,
+      extra_columns,
+      ..
+    } = self.target;
+    let (database, table) = (quote(database), quote(table));
+    if task.fill {
+      return rows
+        .iter()
+        .map(|row| {
+          format!(
+            "UPDATE {database}.{table} SET {} = {} WHERE {} = {} AND {} = {}",
+            quote(&task.text_columns[0]),
+            Value::from(row.texts[0].clone()).to_literal(),
+            self.target.key_tuple(),
+            literal_tuple(&row.keys),
+            self.locale_column,
+            Value::Text(row.locale.to_string()).to_literal()
+          )
+        })
+        .collect();
+    }
+
+    let columns = key_columns
+      .iter()
+      .map(|v| quote(v))
+      .chain([self.locale_column.clone()])
+      .chain(text_columns.iter().chain(extra_columns).map(|v| quote(v)))
+      .collect::<Vec<_>>()
+      .join(", ");
 
-    // Log the execute progress and row affects.
-    info!("{database}.{table} Progress: {i}/{origin_count}");
-    debug!("{database}.{table} Rows affected: {insert_results:?}");
+    rows
+      .iter()
+      .flat_map(|row| {
+        let locale = Value::Text(row.locale.to_string());
+        let values: Vec<_> = row
+          .keys
+          .iter()
+          .cloned()
+          .chain([locale.clone()])
+          .chain(row.texts.iter().cloned().map(Value::from))
+          .chain(row.extras.iter().cloned())
+          .collect();
+        [
+          format!(
+            "DELETE FROM {database}.{table} WHERE {} = {} AND {} = {}",
+            self.target.key_tuple(),
+            literal_tuple(&row.keys),
+            self.locale_column,
+            locale.to_literal()
+          ),
+          format!(
+            "INSERT INTO {database}.{table} ({columns}) VALUES {}",
+            literal_tuple(&values)
+          ),
+        ]
+      })
+      .collect()
   }
+}
+
+#[test]
+fn untranslated_query_test() {
+  use super::{test_target, test_task};
+  use crate::common::ServerType;
 
-  info!("Translate table {database}.{table} from {origin_language} finished (translate rows count: {translate_rows_count}/{origin_count}) ...");
-  Ok(())
+  let target = test_target(&ServerType::AzerothCore, "creature_text_locale");
+  let backend = RowPerLocale::new(&target);
+  let task = test_task(&["Text"], 3);
+
+  // The missing keys are found by an anti-join on all the key columns instead of comparing the locale counts.
+  let sql = backend.untranslated_query("count(*)", &task).into_sql();
+  assert!(sql.ends_with(
+    " AND NOT EXISTS (SELECT 1 FROM `acore_world`.`creature_text_locale` AS `translated` WHERE `translated`.`CreatureID` = `origin`.`CreatureID` AND `translated`.`GroupID` = `origin`.`GroupID` AND `translated`.`ID` = `origin`.`ID` AND `translated`.`Locale` = ?)"
+  ));
+  // All the origin locale rows are selected in overwrite mode.
+  let task = TranslateTask {
+    overwrite: true,
+    ..task
+  };
+  assert!(!backend
+    .untranslated_query("count(*)", &task)
+    .sql()
+    .contains("`translated`"));
 }
 
-async fn translate_table<T: for<'r> sqlx::FromRow<'r, MySqlRow> + Send + Unpin + TranslateLogic>(
-) -> anyhow::Result<()> {
-  let (is_equal, _, (taiwanese_count, chinese_count)) = check_translation::<T>().await?;
-  if !is_equal {
-    translate_language::<T>(Language::Taiwanese, taiwanese_count).await?;
-    translate_language::<T>(Language::Chinese, chinese_count).await?;
-  }
-  Ok(())
+#[test]
+fn build_fill_query_test() {
+  use super::{test_target, test_task};
+  use crate::common::ServerType;
+
+  let target = test_target(&ServerType::AzerothCore, "quest_template_locale");
+  let backend = RowPerLocale::new(&target);
+  let task = TranslateTask {
+    fill: true,
+    ..test_task(&["Details"], 2)
+  };
+
+  // Only the existing target locale rows whose column is empty and the origin column has the text are filled.
+  let sql = backend.untranslated_query("count(*)", &task).into_sql();
+  assert!(
+    sql.contains(" AND `origin`.`Details` IS NOT NULL AND `origin`.`Details` != '' AND EXISTS (")
+  );
+  assert!(sql.contains(" AND (`translated`.`Details` IS NULL OR `translated`.`Details` = '') "));
+
+  // The filled column is updated in place, the other columns of the target locale rows are kept.
+  let rows: Vec<_> = [1, 2]
+    .into_iter()
+    .map(|v| TranslateRow {
+      keys: vec![Value::UInt(v)],
+      locale: Language::Taiwanese,
+      texts: vec![Some(v.to_string())],
+      extras: vec![],
+    })
+    .collect();
+  let sql = backend.build_fill_query(&task, &rows).into_sql();
+  assert!(sql.starts_with("UPDATE `acore_world`.`quest_template_locale` SET `Details` = CASE "));
+  assert!(sql.contains(" END WHERE `locale` = ? AND (`ID`) IN ((?), (?))"));
+  assert_eq!(
+    backend.export_statements(&task, &rows[..1]),
+    ["UPDATE `acore_world`.`quest_template_locale` SET `Details` = '1' WHERE (`ID`) = (1) AND `locale` = 'zhTW'"]
+  );
 }
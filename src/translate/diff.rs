@@ -0,0 +1,255 @@
+use anyhow::bail;
+use log::{debug, info};
+use serde::Serialize;
+
+use crate::common::{selected_targets, ConvertText, LocalePair, ServerType, COMMAND_LINE};
+
+use super::{
+  literal_tuple,
+  report::{DiffReport, DiffRow},
+  schedule::schedule,
+  translate_pairs, with_backend, LocaleSchema, TranslateBackend, TranslateTarget, Value,
+};
+
+/// Define how the converted text differs from the stored text of the other locale.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffKind {
+  Identical,
+  /// Differs only by punctuation, whitespace or a few variant characters.
+  Variant,
+  Different,
+}
+
+/// Check whether the character is a punctuation (ASCII, general or CJK punctuation).
+fn is_punctuation(c: char) -> bool {
+  c.is_ascii_punctuation()
+    || matches!(c,
+      '\u{2010}'..='\u{206F}' // General Punctuation
+      | '\u{3000}'..='\u{303F}' // CJK Symbols and Punctuation
+      | '\u{FE30}'..='\u{FE6F}' // CJK Compatibility Forms and Small Form Variants
+      | '\u{FF01}'..='\u{FF0F}'
+      | '\u{FF1A}'..='\u{FF20}'
+      | '\u{FF3B}'..='\u{FF40}'
+      | '\u{FF5B}'..='\u{FF65}' // Fullwidth punctuation
+    )
+}
+
+/// Remove the punctuations and whitespaces, and fold the fullwidth letters and digits to ASCII.
+fn normalize(text: &str) -> Vec<char> {
+  text
+    .chars()
+    .filter(|&c| !c.is_whitespace() && !is_punctuation(c))
+    .map(|c| match c {
+      '\u{FF10}'..='\u{FF19}' | '\u{FF21}'..='\u{FF3A}' | '\u{FF41}'..='\u{FF5A}' => {
+        char::from_u32(c as u32 - 0xFEE0).unwrap_or(c)
+      }
+      _ => c,
+    })
+    .collect()
+}
+
+impl DiffKind {
+  /// Compare the converted text with the stored text, the texts which have the same length
+  /// and only differ in a few (at most one tenth) non-ASCII characters are treated as variants.
+  pub fn of(converted: &str, stored: &str) -> Self {
+    if converted == stored {
+      return Self::Identical;
+    }
+
+    let (converted, stored) = (normalize(converted), normalize(stored));
+    if converted.len() != stored.len() {
+      return Self::Different;
+    }
+    let differences: Vec<_> = converted
+      .iter()
+      .zip(&stored)
+      .filter(|(a, b)| a != b)
+      .collect();
+    if differences.len() <= (converted.len() / 10).max(1)
+      && differences
+        .iter()
+        .all(|(a, b)| !a.is_ascii() && !b.is_ascii())
+    {
+      Self::Variant
+    } else {
+      Self::Different
+    }
+  }
+}
+
+/// The diff counts of the text column in one conversion direction.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DiffCounts {
+  pub identical: usize,
+  pub variant: usize,
+  pub different: usize,
+}
+
+impl DiffCounts {
+  fn add(&mut self, kind: DiffKind) {
+    match kind {
+      DiffKind::Identical => self.identical += 1,
+      DiffKind::Variant => self.variant += 1,
+      DiffKind::Different => self.different += 1,
+    }
+  }
+
+  fn merge(&mut self, other: &Self) {
+    self.identical += other.identical;
+    self.variant += other.variant;
+    self.different += other.different;
+  }
+}
+
+/// The text columns which are fetched together, the column per locale tables are fetched column by column.
+fn column_groups(target: &TranslateTarget) -> Vec<Vec<String>> {
+  match target.locale {
+    LocaleSchema::Row(_) => vec![target.text_columns.clone()],
+    LocaleSchema::Column(_) => target
+      .text_columns
+      .iter()
+      .map(|v| vec![v.clone()])
+      .collect(),
+  }
+}
+
+/// Diff the text columns in the directions of the locale pairs,
+/// the variant and different rows are added to the report when the report file is set.
+async fn diff_columns<B: TranslateBackend>(
+  backend: &B,
+  pairs: &[LocalePair],
+  text_columns: &[String],
+  report: &mut DiffReport,
+) -> anyhow::Result<DiffCounts> {
+  let TranslateTarget {
+    database, table, ..
+  } = backend.target();
+  // The counts of each column in the direction of each pair.
+  let mut column_counts = vec![vec![DiffCounts::default(); pairs.len()]; text_columns.len()];
+  let keep_rows = COMMAND_LINE.report.is_some();
+  let mut last_keys: Option<Vec<Value>> = None;
+
+  loop {
+    let rows = backend
+      .fetch_pairs(text_columns, last_keys.as_deref())
+      .await?;
+    let Some((last_row, _)) = rows.last() else {
+      break;
+    };
+    if last_keys.as_ref() == Some(&last_row.keys) {
+      bail!("Diff table {database}.{table} {text_columns:?} stalled, the batch keys don't move forward from {:?}!", last_row.keys);
+    }
+    last_keys = Some(last_row.keys.clone());
+
+    for (chinese_row, taiwanese_row) in &rows {
+      for (i, column) in text_columns.iter().enumerate() {
+        for (counts, pair) in column_counts[i].iter_mut().zip(pairs) {
+          let (origin_row, stored_row) = if pair.source() == chinese_row.locale {
+            (chinese_row, taiwanese_row)
+          } else {
+            (taiwanese_row, chinese_row)
+          };
+          let converted = pair.convert_text(&origin_row.texts[i]);
+          let stored = stored_row.texts[i].as_deref().unwrap_or_default();
+          let kind = DiffKind::of(&converted, stored);
+          counts.add(kind);
+          if kind == DiffKind::Different {
+            debug!(
+              "Table {database}.{table} {column} row {:?} {pair} differs: {converted:?} != {stored:?}",
+              origin_row.keys
+            );
+          }
+          if keep_rows && kind != DiffKind::Identical {
+            report.push(
+              kind,
+              DiffRow {
+                database: database.clone(),
+                table: table.clone(),
+                column: column.clone(),
+                origin_language: pair.source(),
+                target_language: pair.target(),
+                keys: literal_tuple(&origin_row.keys),
+                origin_text: origin_row.texts[i].clone().unwrap_or_default(),
+                converted_text: converted,
+                stored_text: stored.to_string(),
+              },
+            );
+          }
+        }
+      }
+    }
+  }
+
+  let mut total_counts = DiffCounts::default();
+  for (column, counts) in text_columns.iter().zip(&column_counts) {
+    for (counts, pair) in counts.iter().zip(pairs) {
+      info!(
+        "Diff table {database}.{table} {column} {pair}: {} identical, {} variant, {} different",
+        counts.identical, counts.variant, counts.different
+      );
+      total_counts.merge(counts);
+    }
+  }
+  Ok(total_counts)
+}
+
+async fn diff_table<B: TranslateBackend>(backend: &B) -> anyhow::Result<(DiffCounts, DiffReport)> {
+  // The directions are narrowed by the table policy, --from and --to in the same way as the translation.
+  let pairs = translate_pairs(backend.target())?;
+  let mut counts = DiffCounts::default();
+  let mut report = DiffReport::default();
+  if pairs.is_empty() {
+    return Ok((counts, report));
+  }
+  for text_columns in column_groups(backend.target()) {
+    counts.merge(&diff_columns(backend, &pairs, &text_columns, &mut report).await?);
+  }
+  Ok((counts, report))
+}
+
+/// Table consistency diff logic, the existing texts of each locale are converted and compared with the other locale,
+/// the variant and different rows are written to the report file when it's set.
+pub async fn diff_tables(server_type: &ServerType) -> anyhow::Result<()> {
+  info!("Diff the existing zhCN and zhTW texts ...");
+  let mut counts = DiffCounts::default();
+  let mut report = DiffReport::default();
+  // The scheduled results keep the registry order in the report.
+  let table_diffs = schedule(
+    selected_targets(server_type)?,
+    COMMAND_LINE.jobs(),
+    |translate_target| async move { with_backend!(translate_target, v => diff_table(v).await) },
+  )
+  .await?;
+  for (table_counts, table_report) in table_diffs {
+    counts.merge(&table_counts);
+    report.merge(table_report);
+  }
+  info!(
+    "Diff finished: {} identical, {} variant (punctuation or variant characters), {} different.",
+    counts.identical, counts.variant, counts.different
+  );
+
+  if let Some(path) = &COMMAND_LINE.report {
+    report.write(path, COMMAND_LINE.report_format)?;
+    info!("The diff report is written to {path:?} ...");
+  }
+  Ok(())
+}
+
+#[test]
+fn diff_kind_test() {
+  assert_eq!(
+    DiffKind::of("你好，世界", "你好，世界"),
+    DiffKind::Identical
+  );
+  assert_eq!(
+    DiffKind::of("你好，世界！", "你好, 世界!"),
+    DiffKind::Variant
+  );
+  assert_eq!(DiffKind::of("ＡＢＣ１２３", "ABC123"), DiffKind::Variant);
+  assert_eq!(DiffKind::of("裡面的東西", "裏面的東西"), DiffKind::Variant);
+  assert_eq!(DiffKind::of("Level 10", "Level 20"), DiffKind::Different);
+  assert_eq!(DiffKind::of("你好世界", "再見世界"), DiffKind::Different);
+  assert_eq!(DiffKind::of("你好", "你好嗎"), DiffKind::Different);
+}
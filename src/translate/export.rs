@@ -0,0 +1,90 @@
+use std::{fs, path::Path};
+
+use anyhow::Context;
+use log::info;
+
+use crate::common::{selected_targets, ServerType};
+
+use super::{plan::plan_table, with_backend, TranslateBackend};
+
+async fn export_table<B: TranslateBackend>(backend: &B) -> anyhow::Result<Vec<String>> {
+  let mut statements = vec![];
+  for task_plan in plan_table(backend).await?.tasks {
+    let task = task_plan.task();
+    let rows: Vec<_> = task_plan
+      .rows
+      .iter()
+      .map(|v| v.converted_row(task.pair))
+      .collect();
+    statements.extend(backend.export_statements(&task, &rows));
+  }
+  Ok(statements)
+}
+
+/// Table translate export logic, the translate SQL is written to the file instead of being executed.
+/// The tables are exported in registry order and the rows in key order, so the file is deterministic.
+pub async fn export_tables(server_type: &ServerType, path: &Path) -> anyhow::Result<()> {
+  info!("Export table translate ...");
+  let mut content =
+    format!("-- The {server_type} locale tables translated by WOW database translator.\n");
+  let mut statements_count = 0;
+
+  for translate_target in selected_targets(server_type)? {
+    let statements = with_backend!(translate_target, v => export_table(v).await)?;
+    if statements.is_empty() {
+      continue;
+    }
+    content.push_str(&format!(
+      "\n-- {}.{}\n",
+      translate_target.database, translate_target.table
+    ));
+    for statement in &statements {
+      content.push_str(statement);
+      content.push_str(";\n");
+    }
+    statements_count += statements.len();
+  }
+
+  fs::write(path, content).with_context(|| format!("Write SQL file {path:?} failed"))?;
+  info!("The translate SQL ({statements_count} statements) is written to {path:?} ...");
+
+  Ok(())
+}
+
+#[test]
+fn export_statements_test() {
+  use super::{test_target, test_task, Language, TranslateRow, Value};
+
+  let row = TranslateRow {
+    keys: vec![Value::UInt(1)],
+    locale: Language::Taiwanese,
+    texts: vec![Some("It's \"1\"\n".into())],
+    extras: vec![],
+  };
+  let task = test_task(&["name"], 1);
+
+  // The texts are escaped as the SQL literals.
+  let target = test_target(&ServerType::Mangos0, "locales_gameobject");
+  let statements =
+    with_backend!(&target, v => v.export_statements(&task, std::slice::from_ref(&row)));
+  assert_eq!(
+    statements,
+    ["UPDATE `mangos0`.`locales_gameobject` SET `name_loc5` = 'It\\'s \\\"1\\\"\\n' WHERE (`entry`) = (1)"]
+  );
+
+  // The AzerothCore rows are replaced by deleting the target locale row first, so the patch can be applied again.
+  let target = test_target(&ServerType::AzerothCore, "gameobject_template_locale");
+  let row = TranslateRow {
+    texts: vec![Some("Name".into()), None],
+    extras: vec![Value::Int(12340)],
+    ..row
+  };
+  let statements = with_backend!(&target, v => v.export_statements(&task, &[row]));
+  assert_eq!(
+    statements,
+    [
+      "DELETE FROM `acore_world`.`gameobject_template_locale` WHERE (`entry`) = (1) AND `locale` = 'zhTW'",
+      "INSERT INTO `acore_world`.`gameobject_template_locale` (`entry`, `locale`, `name`, `castBarCaption`, `VerifiedBuild`) VALUES (1, 'zhTW', 'Name', NULL, 12340)",
+    ]
+  );
+}
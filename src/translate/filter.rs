@@ -0,0 +1,296 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use sqlx::{MySql, QueryBuilder};
+
+use super::{
+  offline::{tokenize, Statement, Token},
+  quote, TranslateTarget, Value,
+};
+
+/// Define the comparison operator of the row filter condition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Operator {
+  Equal,
+  NotEqual,
+  Less,
+  LessEqual,
+  Greater,
+  GreaterEqual,
+  Between,
+  In,
+}
+
+impl Operator {
+  fn symbol(self) -> &'static str {
+    match self {
+      Self::Equal => "=",
+      Self::NotEqual => "!=",
+      Self::Less => "<",
+      Self::LessEqual => "<=",
+      Self::Greater => ">",
+      Self::GreaterEqual => ">=",
+      Self::Between => "BETWEEN",
+      Self::In => "IN",
+    }
+  }
+}
+
+/// The row filter condition on a key or extra column, the values are bound instead of spliced into the SQL.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Condition {
+  pub column: String,
+  pub operator: Operator,
+  pub values: Vec<Value>,
+}
+
+impl Condition {
+  fn parse(statement: &mut Statement) -> anyhow::Result<Self> {
+    let column = statement.name()?;
+    let symbol =
+      |statement: &mut Statement, symbol: char| statement.next_if(|v| *v == Token::Symbol(symbol));
+    let operator = if statement.next_if(|v| v.is_keyword("BETWEEN")) {
+      Operator::Between
+    } else if statement.next_if(|v| v.is_keyword("IN")) {
+      Operator::In
+    } else if symbol(statement, '=') {
+      Operator::Equal
+    } else if symbol(statement, '!') && symbol(statement, '=') {
+      Operator::NotEqual
+    } else if symbol(statement, '<') {
+      if symbol(statement, '=') {
+        Operator::LessEqual
+      } else if symbol(statement, '>') {
+        Operator::NotEqual
+      } else {
+        Operator::Less
+      }
+    } else if symbol(statement, '>') {
+      if symbol(statement, '=') {
+        Operator::GreaterEqual
+      } else {
+        Operator::Greater
+      }
+    } else {
+      bail!(
+        "Unsupported operator {:?} of column {column}",
+        statement.next()
+      );
+    };
+
+    let values = match operator {
+      Operator::Between => {
+        let start = statement.value()?;
+        if !statement.next_if(|v| v.is_keyword("AND")) {
+          bail!("Expect AND of the BETWEEN condition on column {column}");
+        }
+        vec![start, statement.value()?]
+      }
+      Operator::In => statement.list(Statement::value)?,
+      _ => vec![statement.value()?],
+    };
+    if values.contains(&Value::Null) {
+      bail!("The NULL value can't be compared in the condition on column {column}");
+    }
+
+    Ok(Condition {
+      column,
+      operator,
+      values,
+    })
+  }
+
+  /// Push the condition, the column is qualified by the table alias when it's given.
+  fn push(&self, builder: &mut QueryBuilder<'static, MySql>, alias: Option<&str>) {
+    let column = match alias {
+      Some(alias) => format!("{}.{}", quote(alias), quote(&self.column)),
+      None => quote(&self.column),
+    };
+    builder.push(format!(" AND {column} {} ", self.operator.symbol()));
+    match self.operator {
+      Operator::Between => {
+        builder.push_bind(self.values[0].clone());
+        builder.push(" AND ");
+        builder.push_bind(self.values[1].clone());
+      }
+      Operator::In => {
+        builder.push("(");
+        let mut separated = builder.separated(", ");
+        for value in &self.values {
+          separated.push_bind(value.clone());
+        }
+        builder.push(")");
+      }
+      _ => {
+        builder.push_bind(self.values[0].clone());
+      }
+    }
+  }
+}
+
+/// The row filter of the command line: `[TABLE:]CONDITION [AND CONDITION ...]`,
+/// the filter without the table name is applied to all the selected tables.
+#[derive(Clone, Debug)]
+pub struct RowFilter {
+  pub table: Option<String>,
+  pub conditions: Vec<Condition>,
+}
+
+impl FromStr for RowFilter {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let (table, expression) = match s.split_once(':') {
+      Some((table, expression))
+        if !table.is_empty() && table.chars().all(|c| c.is_alphanumeric() || c == '_') =>
+      {
+        (Some(table.to_string()), expression)
+      }
+      _ => (None, s),
+    };
+
+    let tokens = tokenize(expression)?;
+    let mut statement = Statement(&tokens);
+    let mut conditions = vec![Condition::parse(&mut statement)?];
+    while statement.next_if(|v| v.is_keyword("AND")) {
+      conditions.push(Condition::parse(&mut statement)?);
+    }
+    if let Some(token) = statement.next() {
+      bail!("Unexpected {token:?} in the row filter {s:?}");
+    }
+
+    Ok(RowFilter { table, conditions })
+  }
+}
+
+impl TranslateTarget {
+  /// Push the row filter conditions, each condition is joined by AND.
+  pub fn push_filter(&self, builder: &mut QueryBuilder<'static, MySql>, alias: Option<&str>) {
+    for condition in &self.filter {
+      condition.push(builder, alias);
+    }
+  }
+
+  /// The bind parameters count of the row filter conditions.
+  pub fn filter_parameters(&self) -> usize {
+    self.filter.iter().map(|v| v.values.len()).sum()
+  }
+
+  /// Map the filter conditions to the table columns, the column names are case insensitive in MySQL
+  /// so the names in the table definition are used.
+  fn filter_conditions(&self, filter: &RowFilter) -> Result<Vec<Condition>, String> {
+    filter
+      .conditions
+      .iter()
+      .map(|condition| {
+        let Some(column) = self
+          .key_columns
+          .iter()
+          .chain(&self.extra_columns)
+          .find(|v| v.eq_ignore_ascii_case(&condition.column))
+        else {
+          return Err(format!(
+            "Table {} can't be filtered by column {}, the known columns: {:?}",
+            self.table,
+            condition.column,
+            [self.key_columns.clone(), self.extra_columns.clone()].concat()
+          ));
+        };
+        Ok(Condition {
+          column: column.clone(),
+          ..condition.clone()
+        })
+      })
+      .collect()
+  }
+}
+
+/// Add the row filters to the targets, the filter columns must be the key or extra columns of the table.
+/// The filters without the table name are applied to all the selected tables, the tables which don't have
+/// the filter columns are skipped with a warning instead of being translated without the filter.
+pub fn apply_filters(
+  targets: &mut Vec<TranslateTarget>,
+  filters: &[RowFilter],
+) -> anyhow::Result<()> {
+  for filter in filters {
+    if let Some(table) = &filter.table {
+      if !targets.iter().any(|v| &v.table == table) {
+        let names: Vec<_> = targets.iter().map(|v| v.table.as_str()).collect();
+        bail!("The row filter table {table} isn't selected, the selected tables: {names:?}");
+      }
+    }
+
+    let mut skipped_tables = vec![];
+    for target in targets
+      .iter_mut()
+      .filter(|v| filter.table.as_ref().is_none_or(|table| &v.table == table))
+    {
+      match target.filter_conditions(filter) {
+        Ok(conditions) => target.filter.extend(conditions),
+        Err(message) if filter.table.is_none() => {
+          warn!("{message}, skip the table (qualify the filter as TABLE:CONDITION to filter only one table) ...");
+          skipped_tables.push(target.table.clone());
+        }
+        Err(message) => bail!("{message}"),
+      }
+    }
+    if !skipped_tables.is_empty() && skipped_tables.len() == targets.len() {
+      bail!("None of the selected tables can be filtered by the row filter, the skipped tables: {skipped_tables:?}");
+    }
+    targets.retain(|v| !skipped_tables.contains(&v.table));
+  }
+  Ok(())
+}
+
+#[test]
+fn row_filter_test() -> anyhow::Result<()> {
+  use crate::{common::ServerType, data::Registry};
+
+  let filter: RowFilter =
+    "quest_template_locale:id BETWEEN 26000 AND 26100 AND VerifiedBuild >= -1".parse()?;
+  assert_eq!(filter.table.as_deref(), Some("quest_template_locale"));
+  assert_eq!(filter.conditions.len(), 2);
+  assert_eq!(filter.conditions[1].values, [Value::Int(-1)]);
+  let filter: RowFilter = "ID IN (1, 2) AND VerifiedBuild <> 'a:b'".parse()?;
+  assert_eq!(filter.table, None);
+  assert_eq!(filter.conditions[0].operator, Operator::In);
+  assert_eq!(filter.conditions[1].values, [Value::Text("a:b".into())]);
+  assert!("ID = 1; DROP TABLE quest_template_locale"
+    .parse::<RowFilter>()
+    .is_err());
+  assert!("ID = NULL".parse::<RowFilter>().is_err());
+  assert!("ID LIKE 1".parse::<RowFilter>().is_err());
+
+  let mut targets = Registry::load(&[])?.targets(&ServerType::AzerothCore);
+  let filters = [
+    "quest_request_items_locale:id BETWEEN 26000 AND 26100".parse()?,
+    "VerifiedBuild >= 12340".parse()?,
+  ];
+  // The unqualified filter skips the tables which don't have the column.
+  let mut filtered_targets = targets.clone();
+  apply_filters(&mut filtered_targets, &filters[1..])?;
+  assert!(filtered_targets.len() < targets.len());
+  assert!(filtered_targets.iter().all(|v| v.filter.len() == 1));
+  assert!(apply_filters(&mut targets.clone(), &["Unknown = 1".parse()?]).is_err());
+  let mut targets: Vec<_> = targets
+    .drain(..)
+    .filter(|v| v.table == "quest_request_items_locale")
+    .collect();
+  apply_filters(&mut targets, &filters)?;
+  let mut builder =
+    QueryBuilder::new("SELECT 1 FROM `quest_request_items_locale` AS `origin` WHERE 1");
+  targets[0].push_filter(&mut builder, Some("origin"));
+  assert_eq!(
+    builder.sql(),
+    "SELECT 1 FROM `quest_request_items_locale` AS `origin` WHERE 1 AND `origin`.`ID` BETWEEN ? AND ? AND `origin`.`VerifiedBuild` >= ?"
+  );
+  assert!(apply_filters(
+    &mut targets,
+    &["creature_template_locale:entry = 1".parse()?]
+  )
+  .is_err());
+
+  Ok(())
+}
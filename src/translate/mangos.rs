@@ -1,160 +1,518 @@
-use std::collections::HashMap;
+use sqlx::{mysql::MySqlConnection, MySql, QueryBuilder, Row};
 
-use log::*;
-use opencc_rust::OpenCC;
-use sqlx::{MySql, Row};
+use strum::IntoEnumIterator;
 
-use crate::{
-  common::{Language, ServerType, COMMAND_LINE, POOL},
-  data::mangos::{self, get_translate_targets},
+use crate::common::{Language, LocalePair, COMMAND_LINE, POOL};
+
+use super::{
+  literal_tuple, push_tuple, push_tuples, quote, report::LocaleCoverage, LocaleSchema,
+  TranslateBackend, TranslateRow, TranslateTarget, TranslateTask, Value, MAX_BIND_PARAMETERS,
 };
 
-use super::TranslateTarget;
-
-async fn data_count(
-  translate_target: &TranslateTarget,
-  origin_language: Language,
-) -> anyhow::Result<Option<Vec<(String, String, i64)>>> {
-  let TranslateTarget {
-    database,
-    table,
-    locale_columns,
-  } = translate_target;
-  let mut counts = vec![];
-  let mut need_translate = false;
-
-  for locale_column in locale_columns {
-    let origin_locale_column = mangos::column_name(locale_column, origin_language);
-    let target_locale_column = mangos::column_name(locale_column, !origin_language);
-
-    let count: i64 = sqlx::query::<MySql>(&format!(
-      "SELECT count(*) FROM {database}.{table} WHERE {origin_locale_column} IS NOT NULL AND {origin_locale_column} != '' AND ({target_locale_column} IS NULL OR {target_locale_column} = '')"))
-      .fetch_one(&*POOL)
-      .await?
-      .get("count(*)");
+/// The translate backend of the tables which store one column per locale (MaNGOS style),
+/// all the text columns of a row are translated together by updating the empty target locale columns.
+pub struct ColumnPerLocale<'a> {
+  target: &'a TranslateTarget,
+}
 
-    // Only add tables which need to be translated.
-    if count > 0 {
-      need_translate = true;
-      counts.push((origin_locale_column, target_locale_column, count));
+impl<'a> ColumnPerLocale<'a> {
+  pub fn new(target: &'a TranslateTarget) -> Self {
+    if let LocaleSchema::Row(_) = target.locale {
+      unreachable!(
+        "Table {}.{} doesn't store one column per locale",
+        target.database, target.table
+      )
     }
+    ColumnPerLocale { target }
   }
 
-  Ok(if need_translate { Some(counts) } else { None })
-}
+  /// Get the locale column name of the text column, the translated locales always have their locale columns.
+  fn locale_column(&self, text_column: &str, language: Language) -> String {
+    self
+      .target
+      .locale_column(text_column, language)
+      .unwrap_or_else(|| {
+        unreachable!(
+          "Table {}.{} has no {language} locale column",
+          self.target.database, self.target.table
+        )
+      })
+  }
+
+  /// Get the quoted source and target locale column names of the text column.
+  fn locale_columns(&self, text_column: &str, pair: LocalePair) -> (String, String) {
+    (
+      quote(&self.locale_column(text_column, pair.source())),
+      quote(&self.locale_column(text_column, pair.target())),
+    )
+  }
+
+  /// Build the condition of the rows which have the text in the locale column.
+  fn has_text_condition(&self, text_column: &str, language: Language) -> String {
+    let locale_column = quote(&self.locale_column(text_column, language));
+    format!("{locale_column} IS NOT NULL AND {locale_column} != ''")
+  }
 
-async fn check_translation(
-  translate_target: &TranslateTarget,
-) -> anyhow::Result<HashMap<Language, Vec<(String, String, i64)>>> {
-  let TranslateTarget {
-    database, table, ..
-  } = translate_target;
-  let mut need_translate_content = HashMap::new();
+  /// Build the multi-row update SQL of all the text columns:
+  /// UPDATE ... SET column = CASE WHEN (keys) = (...) THEN ... ELSE column END, ... WHERE (keys) IN ((...), ...)
+  /// The empty converted texts are skipped, they're the text columns which don't need to be translated.
+  fn build_update_query(
+    &self,
+    task: &TranslateTask,
+    rows: &[TranslateRow],
+  ) -> QueryBuilder<'static, MySql> {
+    let TranslateTarget {
+      database, table, ..
+    } = self.target;
+    let keys = self.target.key_tuple();
 
-  let taiwanese_count = data_count(translate_target, Language::Taiwanese).await?;
-  let chinese_count = data_count(translate_target, Language::Chinese).await?;
+    let mut builder =
+      QueryBuilder::new(format!("UPDATE {}.{} SET ", quote(database), quote(table)));
+    let mut first_column = true;
+    for (i, text_column) in task.text_columns.iter().enumerate() {
+      let mut rows = rows
+        .iter()
+        .filter_map(|v| Some((&v.keys, v.texts[i].as_ref().filter(|v| !v.is_empty())?)))
+        .peekable();
+      if rows.peek().is_none() {
+        continue;
+      }
+      let (_, target_locale_column) = self.locale_columns(text_column, task.pair);
+      if !first_column {
+        builder.push(", ");
+      }
+      first_column = false;
+      builder.push(format!("{target_locale_column} = CASE"));
+      for (row_keys, text) in rows {
+        builder.push(format!(" WHEN {keys} = "));
+        push_tuple(&mut builder, row_keys);
+        builder.push(" THEN ").push_bind(text.clone());
+      }
+      builder.push(format!(" ELSE {target_locale_column} END"));
+    }
+    builder.push(format!(" WHERE {keys} IN "));
+    push_tuples(&mut builder, rows.iter().map(|v| v.keys.as_slice()));
+    self.target.push_filter(&mut builder, None);
 
-  let need_translate = !need_translate_content.is_empty();
-  info!("Table {database}.{table} has untranslate contents: {need_translate} (taiwanese count: {taiwanese_count:?}, chinese count: {chinese_count:?}) ... ");
+    builder
+  }
 
-  if let Some(taiwanese_count) = taiwanese_count {
-    need_translate_content.insert(Language::Taiwanese, taiwanese_count);
+  /// Build the condition of the text column which has the origin locale text but the empty target locale text,
+  /// the target locale text isn't checked in overwrite mode.
+  fn untranslated_condition(&self, text_column: &str, pair: LocalePair, overwrite: bool) -> String {
+    let (_, target_locale_column) = self.locale_columns(text_column, pair);
+    let mut condition = self.has_text_condition(text_column, pair.source());
+    if !overwrite {
+      condition.push_str(&format!(
+        " AND ({target_locale_column} IS NULL OR {target_locale_column} = '')"
+      ));
+    }
+    condition
   }
-  if let Some(chinese_count) = chinese_count {
-    need_translate_content.insert(Language::Chinese, chinese_count);
+
+  /// Build the condition of the rows which have any untranslated text column of the task.
+  fn untranslated_rows_condition(&self, task: &TranslateTask) -> String {
+    task
+      .text_columns
+      .iter()
+      .map(|v| {
+        format!(
+          "({})",
+          self.untranslated_condition(v, task.pair, task.overwrite)
+        )
+      })
+      .collect::<Vec<_>>()
+      .join(" OR ")
   }
 
-  Ok(need_translate_content)
-}
+  /// Build the query of the text columns in the locale with the condition,
+  /// each text column is selected only when its column condition is matched, otherwise it's selected as the default text.
+  fn locale_query(
+    &self,
+    task: &TranslateTask,
+    language: Language,
+    column_condition: impl Fn(&str) -> String,
+    default_text: &str,
+    condition: &str,
+  ) -> QueryBuilder<'static, MySql> {
+    let TranslateTarget {
+      database, table, ..
+    } = self.target;
+    let columns = task
+      .text_columns
+      .iter()
+      .map(|v| {
+        let locale_column = quote(&self.locale_column(v, language));
+        format!(
+          "IF({}, {locale_column}, {default_text}) AS {locale_column}",
+          column_condition(v)
+        )
+      })
+      .collect::<Vec<_>>()
+      .join(", ");
 
-/// Table translation check logic.
-pub async fn check_translations(server_type: &ServerType) -> anyhow::Result<()> {
-  info!("Check table translations ...");
-  let mut need_translate_tables = vec![];
-  let translate_targets = get_translate_targets(server_type);
+    let mut builder = QueryBuilder::new(format!(
+      "SELECT {},{columns} FROM {}.{} WHERE ({condition})",
+      self.target.key_order(),
+      quote(database),
+      quote(table),
+    ));
+    self.target.push_filter(&mut builder, None);
+    builder
+  }
 
-  for translate_target in &translate_targets {
-    let need_translate_content = check_translation(translate_target).await?;
-    if !need_translate_content.is_empty() {
-      need_translate_tables.push(&translate_target.table);
-    }
+  /// Build the query of the untranslated rows, the text columns which don't need to be translated are selected as NULL.
+  fn untranslated_query(&self, task: &TranslateTask) -> QueryBuilder<'static, MySql> {
+    self.locale_query(
+      task,
+      task.pair.source(),
+      |v| self.untranslated_condition(v, task.pair, task.overwrite),
+      "NULL",
+      &self.untranslated_rows_condition(task),
+    )
   }
 
-  if need_translate_tables.is_empty() {
-    info!("All tables' translation count are equal.");
-  } else {
-    info!("Some tables' translation count aren't equal: {need_translate_tables:?}.");
+  async fn fetch_locale_rows(
+    &self,
+    task: &TranslateTask,
+    language: Language,
+    mut builder: QueryBuilder<'static, MySql>,
+  ) -> anyhow::Result<Vec<TranslateRow>> {
+    let text_columns = self.text_columns(task, language);
+    builder
+      .build()
+      .fetch_all(&*POOL)
+      .await?
+      .iter()
+      .map(|v| TranslateRow::decode(v, language, &self.target.key_columns, &text_columns, &[]))
+      .collect()
   }
 
-  Ok(())
+  /// Get the locale column names of the task text columns.
+  fn text_columns(&self, task: &TranslateTask, language: Language) -> Vec<String> {
+    task
+      .text_columns
+      .iter()
+      .map(|v| self.locale_column(v, language))
+      .collect()
+  }
 }
 
-async fn translate_column(
-  translate_target: &TranslateTarget,
-  origin_language: Language,
-  origin_count: i64,
-  origin_locale_column: &String,
-  target_locale_column: &String,
-) -> anyhow::Result<()> {
-  let TranslateTarget {
-    database, table, ..
-  } = translate_target;
-  info!(
-    "Translating table {database}.{table} from {origin_language} (total count: {origin_count}) ..."
-  );
+impl TranslateBackend for ColumnPerLocale<'_> {
+  fn target(&self) -> &TranslateTarget {
+    self.target
+  }
+
+  // The untranslated rows and each text column are counted in one query.
+  async fn count_untranslated(
+    &self,
+    pair: LocalePair,
+    overwrite: bool,
+  ) -> anyhow::Result<Vec<TranslateTask>> {
+    let TranslateTarget {
+      database,
+      table,
+      text_columns,
+      ..
+    } = self.target;
+    let mut task = TranslateTask {
+      pair,
+      text_columns: text_columns.clone(),
+      count: 0,
+      column_counts: vec![],
+      overwrite,
+      fill: false,
+    };
+    let counts = text_columns
+      .iter()
+      .map(|v| {
+        format!(
+          "count(CASE WHEN {} THEN 1 END) AS {}",
+          self.untranslated_condition(v, pair, overwrite),
+          quote(v)
+        )
+      })
+      .collect::<Vec<_>>()
+      .join(", ");
 
-  let (mut translate_rows_count, batch_size) = (0, COMMAND_LINE.batch_size);
-  for i in (0..origin_count).step_by(COMMAND_LINE.batch_size) {
-    let results = sqlx::query::<MySql>(&format!(
-      "SELECT entry,{origin_locale_column},{target_locale_column} FROM {database}.{table} WHERE {origin_locale_column} IS NOT NULL AND {origin_locale_column} != '' AND ({target_locale_column} IS NULL OR {target_locale_column} = '') LIMIT {batch_size}"
-    ))
-    .fetch_all(&*POOL)
-    .await?;
-
-    let mut insert_results = vec![];
-    for v in results {
-      let entry: u32 = v.get("entry");
-      let origin_text: String = v.get(origin_locale_column.as_str());
-      let opencc: &OpenCC = origin_language.into();
-      let target_text = opencc.convert(origin_text);
-
-      // Execute the insert SQL.
-      let rows_affected = sqlx::query(&format!(
-        "UPDATE {database}.{table} SET {target_locale_column} = ? WHERE entry = {entry}"
-      ))
-      .bind(target_text)
-      .execute(&*POOL)
+    let mut builder = QueryBuilder::new(format!(
+      "SELECT count(*), {counts} FROM {}.{} WHERE ({})",
+      quote(database),
+      quote(table),
+      self.untranslated_rows_condition(&task)
+    ));
+    self.target.push_filter(&mut builder, None);
+    let row = builder.build().fetch_one(&*POOL).await?;
+    task.count = row.try_get("count(*)")?;
+    let column_counts = text_columns
+      .iter()
+      .map(|v| row.try_get(v.as_str()))
+      .collect::<Result<Vec<i64>, _>>()?;
+
+    // Only add columns which need to be translated.
+    (task.text_columns, task.column_counts) = text_columns
+      .iter()
+      .cloned()
+      .zip(column_counts)
+      .filter(|(_, count)| *count > 0)
+      .unzip();
+
+    Ok(if task.count > 0 { vec![task] } else { vec![] })
+  }
+
+  // The rows which have the text in any text column of the locale are counted, all the locales are counted in one query.
+  async fn count_locales(&self) -> anyhow::Result<Vec<LocaleCoverage>> {
+    let TranslateTarget {
+      database,
+      table,
+      text_columns,
+      ..
+    } = self.target;
+    // The enUS texts are stored in the base tables instead of the locale columns.
+    let locales: Vec<_> = Language::iter()
+      .filter(|v| *v != Language::English)
+      .collect();
+    let counts = locales
+      .iter()
+      .map(|&language| {
+        let condition = text_columns
+          .iter()
+          .map(|v| format!("({})", self.has_text_condition(v, language)))
+          .collect::<Vec<_>>()
+          .join(" OR ");
+        format!(
+          "count(CASE WHEN {condition} THEN 1 END) AS {}",
+          quote(&language.to_string())
+        )
+      })
+      .collect::<Vec<_>>()
+      .join(", ");
+
+    let mut builder = QueryBuilder::new(format!(
+      "SELECT {counts} FROM {}.{} WHERE 1",
+      quote(database),
+      quote(table)
+    ));
+    self.target.push_filter(&mut builder, None);
+    let row = builder.build().fetch_one(&*POOL).await?;
+    locales
+      .into_iter()
+      .map(|locale| {
+        let rows = row.try_get(locale.to_string().as_str())?;
+        Ok(LocaleCoverage { locale, rows })
+      })
+      .collect()
+  }
+
+  async fn fetch_batch(
+    &self,
+    task: &TranslateTask,
+    after: Option<&[Value]>,
+    limit: usize,
+  ) -> anyhow::Result<Vec<TranslateRow>> {
+    let mut builder = self.untranslated_query(task);
+    self.target.push_keyset(&mut builder, after);
+    builder.push(format!(
+      " ORDER BY {} LIMIT {}",
+      self.target.key_order(),
+      limit
+    ));
+    self
+      .fetch_locale_rows(task, task.pair.source(), builder)
+      .await
+  }
+
+  async fn fetch_rows(
+    &self,
+    task: &TranslateTask,
+    keys: &[Vec<Value>],
+  ) -> anyhow::Result<Vec<TranslateRow>> {
+    let mut builder = self.untranslated_query(task);
+    builder.push(format!(" AND {} IN ", self.target.key_tuple()));
+    push_tuples(&mut builder, keys.iter().map(Vec::as_slice));
+    self
+      .fetch_locale_rows(task, task.pair.source(), builder)
+      .await
+  }
+
+  async fn fetch_pairs(
+    &self,
+    text_columns: &[String],
+    after: Option<&[Value]>,
+  ) -> anyhow::Result<Vec<(TranslateRow, TranslateRow)>> {
+    let TranslateTarget {
+      database,
+      table,
+      key_columns,
+      ..
+    } = self.target;
+    let (chinese_locale_column, taiwanese_locale_column) =
+      self.locale_columns(&text_columns[0], LocalePair::CHINESE[1]);
+
+    let mut builder = QueryBuilder::new(format!(
+      "SELECT {},{chinese_locale_column},{taiwanese_locale_column} FROM {}.{} WHERE {chinese_locale_column} IS NOT NULL AND {chinese_locale_column} != '' AND {taiwanese_locale_column} IS NOT NULL AND {taiwanese_locale_column} != ''",
+      self.target.key_order(),
+      quote(database),
+      quote(table),
+    ));
+    self.target.push_filter(&mut builder, None);
+    self.target.push_keyset(&mut builder, after);
+    builder.push(format!(
+      " ORDER BY {} LIMIT {}",
+      self.target.key_order(),
+      COMMAND_LINE.batch_size
+    ));
+
+    builder
+      .build()
+      .fetch_all(&*POOL)
       .await?
-      .rows_affected();
+      .iter()
+      .map(|v| {
+        let [chinese_row, taiwanese_row] =
+          [Language::Chinese, Language::Taiwanese].map(|language| {
+            TranslateRow::decode(
+              v,
+              language,
+              key_columns,
+              &[self.locale_column(&text_columns[0], language)],
+              &[],
+            )
+          });
+        Ok((chinese_row?, taiwanese_row?))
+      })
+      .collect()
+  }
 
-      insert_results.push(rows_affected);
-      translate_rows_count += rows_affected;
-    }
+  // The text columns which aren't translated from the source locale texts are fetched as the empty texts,
+  // so they're the same as the skipped converted texts.
+  async fn fetch_translated(
+    &self,
+    task: &TranslateTask,
+    keys: &[Vec<Value>],
+  ) -> anyhow::Result<Vec<TranslateRow>> {
+    let condition = task
+      .text_columns
+      .iter()
+      .map(|v| format!("({})", self.has_text_condition(v, task.pair.target())))
+      .collect::<Vec<_>>()
+      .join(" OR ");
+    let mut builder = self.locale_query(
+      task,
+      task.pair.target(),
+      |v| self.untranslated_condition(v, task.pair, true),
+      "''",
+      &condition,
+    );
+    builder.push(format!(" AND {} IN ", self.target.key_tuple()));
+    push_tuples(&mut builder, keys.iter().map(Vec::as_slice));
+    self
+      .fetch_locale_rows(task, task.pair.target(), builder)
+      .await
+  }
 
-    // Log the execute progress and row affects.
-    info!("{database}.{table} Progress: {i}/{origin_count}");
-    debug!("{database}.{table} Rows affected: {insert_results:?}");
+  async fn write_batch(
+    &self,
+    connection: &mut MySqlConnection,
+    task: &TranslateTask,
+    rows: Vec<TranslateRow>,
+  ) -> anyhow::Result<u64> {
+    let keys_count = self.target.key_columns.len();
+    let row_parameters = keys_count + (keys_count + 1) * task.text_columns.len();
+    let mut rows_affected = 0;
+    let free_parameters = MAX_BIND_PARAMETERS - self.target.filter_parameters();
+    for rows in rows.chunks(free_parameters / row_parameters) {
+      rows_affected += self
+        .build_update_query(task, rows)
+        .build()
+        .execute(&mut *connection)
+        .await?
+        .rows_affected();
+    }
+    Ok(rows_affected)
   }
 
-  info!("Translate table {database}.{table} from {origin_locale_column} to {target_locale_column} finished (translate rows count: {translate_rows_count}/{origin_count}) ...");
+  fn export_statements(&self, task: &TranslateTask, rows: &[TranslateRow]) -> Vec<String> {
+    let TranslateTarget {
+      database, table, ..
+    } = self.target;
 
-  Ok(())
+    rows
+      .iter()
+      .filter_map(|row| {
+        let updates = task
+          .text_columns
+          .iter()
+          .zip(&row.texts)
+          .filter_map(|(text_column, text)| {
+            let text = text.as_ref().filter(|v| !v.is_empty())?;
+            let (_, target_locale_column) = self.locale_columns(text_column, task.pair);
+            Some(format!(
+              "{target_locale_column} = {}",
+              Value::from(text.clone()).to_literal()
+            ))
+          })
+          .collect::<Vec<_>>();
+        (!updates.is_empty()).then(|| {
+          format!(
+            "UPDATE {}.{} SET {} WHERE {} = {}",
+            quote(database),
+            quote(table),
+            updates.join(", "),
+            self.target.key_tuple(),
+            literal_tuple(&row.keys)
+          )
+        })
+      })
+      .collect()
+  }
 }
 
-pub async fn translate_tables(server_type: &ServerType) -> anyhow::Result<()> {
-  for translate_target in &mangos::get_translate_targets(server_type) {
-    for (language, columns) in check_translation(translate_target).await? {
-      for (origin_locale_column, target_locale_column, count) in columns {
-        translate_column(
-          translate_target,
-          language,
-          count,
-          &origin_locale_column,
-          &target_locale_column,
-        )
-        .await?;
-      }
-    }
+#[test]
+fn build_update_query_test() {
+  use super::{test_target, test_task};
+  use crate::common::ServerType;
+
+  let target = test_target(&ServerType::Mangos0, "locales_quest");
+  let backend = ColumnPerLocale::new(&target);
+  let task = TranslateTask {
+    column_counts: vec![2, 1, 0],
+    ..test_task(&["Title", "Details", "EndText"], 2)
+  };
+
+  // All the untranslated locale columns of a row are fetched by one query.
+  let sql = backend.untranslated_query(&task).into_sql();
+  assert_eq!(sql.matches("SELECT ").count(), 1);
+  for column in ["Title", "Details", "EndText"] {
+    assert!(sql.contains(&format!(" AS `{column}_loc4`")));
   }
-  Ok(())
+
+  // The empty converted texts are the text columns which don't need to be translated,
+  // the other columns of the batch are written by one UPDATE.
+  let rows: Vec<_> = [1, 2]
+    .into_iter()
+    .map(|v| TranslateRow {
+      keys: vec![Value::UInt(v)],
+      locale: Language::Taiwanese,
+      texts: vec![
+        Some(v.to_string()),
+        Some(if v == 1 { "Details".into() } else { "".into() }),
+        Some("".into()),
+      ],
+      extras: vec![],
+    })
+    .collect();
+  let sql = backend.build_update_query(&task, &rows).into_sql();
+  assert_eq!(sql.matches("UPDATE ").count(), 1);
+  assert!(
+    sql.contains(" `Details_loc5` = CASE WHEN (`entry`) = (?) THEN ? ELSE `Details_loc5` END ")
+  );
+  assert!(!sql.contains("`EndText_loc5`"));
+  assert_eq!(
+    backend.export_statements(&task, &rows),
+    [
+      "UPDATE `mangos0`.`locales_quest` SET `Title_loc5` = '1', `Details_loc5` = 'Details' WHERE (`entry`) = (1)",
+      "UPDATE `mangos0`.`locales_quest` SET `Title_loc5` = '2' WHERE (`entry`) = (2)",
+    ]
+  );
 }
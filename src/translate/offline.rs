@@ -0,0 +1,624 @@
+use std::{
+  collections::{HashMap, HashSet},
+  fs,
+  iter::Peekable,
+  path::Path,
+  path::PathBuf,
+  str::Chars,
+};
+
+use anyhow::{bail, Context};
+use log::{debug, info};
+
+use crate::common::{selected_targets, ConvertText, LocalePair, ServerType};
+
+use super::{
+  literal_tuple, quote, translate_pairs, LocaleSchema, TranslateRow, TranslateTarget, Value,
+};
+
+/// The token of the SQL dump file, the comments and whitespaces are skipped.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Token {
+  Word(String),
+  Identifier(String),
+  Text(String),
+  Number(String),
+  Symbol(char),
+}
+
+impl Token {
+  pub(super) fn is_keyword(&self, keyword: &str) -> bool {
+    matches!(self, Self::Word(v) if v.eq_ignore_ascii_case(keyword))
+  }
+}
+
+fn skip_line(chars: &mut Peekable<Chars>) {
+  for c in chars.by_ref() {
+    if c == '\n' {
+      break;
+    }
+  }
+}
+
+fn read_quoted(chars: &mut Peekable<Chars>, quote: char) -> anyhow::Result<String> {
+  let mut text = String::new();
+  loop {
+    match chars.next() {
+      // The quote is escaped by doubling it.
+      Some(c) if c == quote && chars.peek() == Some(&quote) => {
+        chars.next();
+        text.push(c);
+      }
+      Some(c) if c == quote => return Ok(text),
+      Some('\\') if quote != '`' => match chars.next() {
+        Some('0') => text.push('\0'),
+        Some('n') => text.push('\n'),
+        Some('r') => text.push('\r'),
+        Some('t') => text.push('\t'),
+        Some('b') => text.push('\x08'),
+        Some('Z') => text.push('\x1a'),
+        Some(c) => text.push(c),
+        None => break,
+      },
+      Some(c) => text.push(c),
+      None => break,
+    }
+  }
+  bail!("Unterminated quoted text {quote}{text}");
+}
+
+pub(super) fn tokenize(content: &str) -> anyhow::Result<Vec<Token>> {
+  let mut tokens = vec![];
+  let mut chars = content.chars().peekable();
+
+  while let Some(c) = chars.next() {
+    match c {
+      _ if c.is_whitespace() => {}
+      '#' => skip_line(&mut chars),
+      '-' if chars.peek() == Some(&'-') => skip_line(&mut chars),
+      '/' if chars.peek() == Some(&'*') => {
+        chars.next();
+        let mut last = ' ';
+        for c in chars.by_ref() {
+          if last == '*' && c == '/' {
+            break;
+          }
+          last = c;
+        }
+      }
+      '`' => tokens.push(Token::Identifier(read_quoted(&mut chars, c)?)),
+      '\'' | '"' => tokens.push(Token::Text(read_quoted(&mut chars, c)?)),
+      _ if c.is_ascii_digit() => {
+        let mut number = c.to_string();
+        while let Some(&c) = chars.peek() {
+          if !(c.is_ascii_alphanumeric() || c == '.') {
+            break;
+          }
+          number.push(c);
+          chars.next();
+        }
+        tokens.push(Token::Number(number));
+      }
+      _ if c.is_alphabetic() || c == '_' => {
+        let mut word = c.to_string();
+        while let Some(&c) = chars.peek() {
+          if !(c.is_alphanumeric() || c == '_') {
+            break;
+          }
+          word.push(c);
+          chars.next();
+        }
+        tokens.push(Token::Word(word));
+      }
+      _ => tokens.push(Token::Symbol(c)),
+    }
+  }
+
+  Ok(tokens)
+}
+
+/// The table rows parsed from the INSERT statements of the SQL dump files.
+#[derive(Clone, Debug, Default)]
+pub struct DumpTable {
+  pub name: String,
+  pub columns: Vec<String>,
+  pub rows: Vec<Vec<Value>>,
+}
+
+impl DumpTable {
+  fn column_index(&self, column: &str) -> anyhow::Result<usize> {
+    // The column names are case insensitive in MySQL.
+    self
+      .columns
+      .iter()
+      .position(|v| v.eq_ignore_ascii_case(column))
+      .with_context(|| format!("Table {} doesn't have column {column}", self.name))
+  }
+
+  fn column_indexes(&self, columns: &[String]) -> anyhow::Result<Vec<usize>> {
+    columns.iter().map(|v| self.column_index(v)).collect()
+  }
+
+  /// Build the SQL which replaces all the rows of the table.
+  pub fn to_sql(&self) -> String {
+    let table = quote(&self.name);
+    let mut sql = format!("DELETE FROM {table};\n");
+    if !self.rows.is_empty() {
+      let columns = self
+        .columns
+        .iter()
+        .map(|v| quote(v))
+        .collect::<Vec<_>>()
+        .join(", ");
+      let rows = self
+        .rows
+        .iter()
+        .map(|v| literal_tuple(v))
+        .collect::<Vec<_>>()
+        .join(",\n");
+      sql.push_str(&format!(
+        "INSERT INTO {table} ({columns}) VALUES\n{rows};\n"
+      ));
+    }
+    sql
+  }
+}
+
+/// The statement tokens parser, the tokens are consumed from the front.
+pub(super) struct Statement<'a>(pub(super) &'a [Token]);
+
+impl Statement<'_> {
+  pub(super) fn next(&mut self) -> Option<&Token> {
+    let (token, tokens) = self.0.split_first()?;
+    self.0 = tokens;
+    Some(token)
+  }
+
+  pub(super) fn next_if(&mut self, predicate: impl FnOnce(&Token) -> bool) -> bool {
+    match self.0.first() {
+      Some(token) if predicate(token) => {
+        self.0 = &self.0[1..];
+        true
+      }
+      _ => false,
+    }
+  }
+
+  fn skip_keywords(&mut self, keywords: &[&str]) {
+    while self.next_if(|v| keywords.iter().any(|keyword| v.is_keyword(keyword))) {}
+  }
+
+  fn expect_symbol(&mut self, symbol: char) -> anyhow::Result<()> {
+    match self.next() {
+      Some(Token::Symbol(v)) if *v == symbol => Ok(()),
+      token => bail!("Expect symbol {symbol} but found {token:?}"),
+    }
+  }
+
+  pub(super) fn name(&mut self) -> anyhow::Result<String> {
+    match self.next() {
+      Some(Token::Identifier(v) | Token::Word(v)) => Ok(v.clone()),
+      token => bail!("Expect name but found {token:?}"),
+    }
+  }
+
+  /// Parse the table name, the database prefix is ignored.
+  fn table_name(&mut self) -> anyhow::Result<String> {
+    let mut name = self.name()?;
+    while self.next_if(|v| *v == Token::Symbol('.')) {
+      name = self.name()?;
+    }
+    Ok(name)
+  }
+
+  pub(super) fn value(&mut self) -> anyhow::Result<Value> {
+    let negative = self.next_if(|v| *v == Token::Symbol('-'));
+    Ok(match self.next() {
+      Some(Token::Word(v)) if v.eq_ignore_ascii_case("NULL") => Value::Null,
+      Some(Token::Text(v)) => Value::Text(v.clone()),
+      Some(Token::Number(v)) if negative => v
+        .parse::<i64>()
+        .map_or(Value::Text(format!("-{v}")), |v| Value::Int(-v)),
+      Some(Token::Number(v)) => v.parse().map_or(Value::Text(v.clone()), Value::UInt),
+      token => bail!("Unsupported value {token:?}"),
+    })
+  }
+
+  /// Parse the comma separated list in the parentheses.
+  pub(super) fn list<T>(
+    &mut self,
+    mut item: impl FnMut(&mut Self) -> anyhow::Result<T>,
+  ) -> anyhow::Result<Vec<T>> {
+    self.expect_symbol('(')?;
+    let mut items = vec![item(self)?];
+    while self.next_if(|v| *v == Token::Symbol(',')) {
+      items.push(item(self)?);
+    }
+    self.expect_symbol(')')?;
+    Ok(items)
+  }
+
+  /// Parse the column names of the CREATE TABLE statement, the index definitions are ignored.
+  fn create_table(&mut self) -> anyhow::Result<DumpTable> {
+    self.skip_keywords(&["IF", "NOT", "EXISTS"]);
+    let name = self.table_name()?;
+    self.expect_symbol('(')?;
+
+    let mut columns = vec![];
+    let mut depth = 1;
+    let mut definition_start = true;
+    while depth > 0 {
+      match self.next() {
+        Some(Token::Symbol('(')) => depth += 1,
+        Some(Token::Symbol(')')) => depth -= 1,
+        Some(Token::Symbol(',')) if depth == 1 => {
+          definition_start = true;
+          continue;
+        }
+        Some(Token::Identifier(v)) if definition_start => columns.push(v.clone()),
+        Some(_) => {}
+        None => bail!("Unterminated CREATE TABLE {name}"),
+      }
+      definition_start = false;
+    }
+
+    Ok(DumpTable {
+      name,
+      columns,
+      rows: vec![],
+    })
+  }
+
+  /// Parse the INSERT or REPLACE statement, the columns are empty when the statement has no column list.
+  fn insert(&mut self) -> anyhow::Result<DumpTable> {
+    self.skip_keywords(&["LOW_PRIORITY", "DELAYED", "HIGH_PRIORITY", "IGNORE", "INTO"]);
+    let name = self.table_name()?;
+    let columns = match self.0.first() {
+      Some(Token::Symbol('(')) => self.list(Self::name)?,
+      _ => vec![],
+    };
+    if !self.next_if(|v| v.is_keyword("VALUES") || v.is_keyword("VALUE")) {
+      bail!("Only INSERT ... VALUES statements are supported by table {name}");
+    }
+
+    let mut rows = vec![self.list(Self::value)?];
+    while self.next_if(|v| *v == Token::Symbol(',')) {
+      rows.push(self.list(Self::value)?);
+    }
+
+    Ok(DumpTable {
+      name,
+      columns,
+      rows,
+    })
+  }
+}
+
+/// Parse the tables of the SQL dump, the rows of the same table are merged.
+pub fn parse_dump(content: &str, tables: &mut Vec<DumpTable>) -> anyhow::Result<()> {
+  let tokens = tokenize(content)?;
+  for statement in tokens.split(|v| *v == Token::Symbol(';')) {
+    let mut statement = Statement(statement);
+    let Some(first_token) = statement.next().cloned() else {
+      continue;
+    };
+
+    let mut dump_table =
+      if first_token.is_keyword("CREATE") && statement.next_if(|v| v.is_keyword("TABLE")) {
+        statement.create_table()?
+      } else if first_token.is_keyword("INSERT") || first_token.is_keyword("REPLACE") {
+        statement.insert()?
+      } else {
+        continue;
+      };
+
+    match tables.iter_mut().find(|v| v.name == dump_table.name) {
+      Some(table) => {
+        if dump_table.columns.is_empty() {
+          dump_table.columns = table.columns.clone();
+        } else if table.columns.is_empty() || table.rows.is_empty() {
+          table.columns = dump_table.columns.clone();
+        }
+        if dump_table.columns != table.columns {
+          bail!(
+            "Table {} INSERT columns {:?} don't match the columns {:?}",
+            table.name,
+            dump_table.columns,
+            table.columns
+          );
+        }
+        table.rows.extend(dump_table.rows);
+      }
+      None => tables.push(dump_table),
+    }
+  }
+
+  for table in tables.iter() {
+    if let Some(row) = table.rows.iter().find(|v| v.len() != table.columns.len()) {
+      bail!(
+        "Table {} row {row:?} doesn't match the columns {:?}",
+        table.name,
+        table.columns
+      );
+    }
+  }
+
+  Ok(())
+}
+
+fn text(value: &Value) -> Option<String> {
+  match value {
+    Value::Null => None,
+    Value::Text(v) => Some(v.clone()),
+    v => Some(v.to_literal()),
+  }
+}
+
+fn is_empty(value: &Value) -> bool {
+  match value {
+    Value::Null => true,
+    Value::Text(v) => v.is_empty(),
+    _ => false,
+  }
+}
+
+/// Translate the table which stores one row per locale, the rows missing in the target locale are appended
+/// and the empty text columns of the existing target locale rows are filled.
+fn translate_row_table(
+  target: &TranslateTarget,
+  table: &mut DumpTable,
+  pairs: &[LocalePair],
+) -> anyhow::Result<usize> {
+  let LocaleSchema::Row(locale_column) = &target.locale else {
+    unreachable!("Table {} doesn't store one row per locale", target.table)
+  };
+  let locale_index = table.column_index(locale_column)?;
+  let key_indexes = table.column_indexes(&target.key_columns)?;
+  let text_indexes = table.column_indexes(&target.text_columns)?;
+  let extra_indexes = table.column_indexes(&target.extra_columns)?;
+  let row_keys =
+    |row: &[Value]| -> Vec<Value> { key_indexes.iter().map(|&i| row[i].clone()).collect() };
+
+  let mut filled_rows = HashSet::new();
+  for &pair in pairs {
+    let (origin_locale, target_locale) = (
+      Value::Text(pair.source().to_string()),
+      Value::Text(pair.target().to_string()),
+    );
+    let origin_texts: HashMap<_, Vec<_>> = table
+      .rows
+      .iter()
+      .filter(|v| v[locale_index] == origin_locale)
+      .map(|v| {
+        let texts = text_indexes.iter().map(|&i| v[i].clone()).collect();
+        (literal_tuple(&row_keys(v)), texts)
+      })
+      .collect();
+
+    for (index, row) in table.rows.iter_mut().enumerate() {
+      if row[locale_index] != target_locale {
+        continue;
+      }
+      let Some(texts) = origin_texts.get(&literal_tuple(&row_keys(row))) else {
+        continue;
+      };
+      for (&i, origin_text) in text_indexes.iter().zip(texts) {
+        if is_empty(&row[i]) && !is_empty(origin_text) {
+          row[i] = Value::Text(pair.convert_text(&text(origin_text)));
+          filled_rows.insert(index);
+        }
+      }
+    }
+  }
+
+  let mut new_rows: Vec<Vec<Value>> = vec![];
+  for &pair in pairs {
+    let target_locale = Value::Text(pair.target().to_string());
+    let mut translated_keys: HashSet<_> = table
+      .rows
+      .iter()
+      .chain(&new_rows)
+      .filter(|v| v[locale_index] == target_locale)
+      .map(|v| literal_tuple(&row_keys(v)))
+      .collect();
+
+    for row in &table.rows {
+      if row[locale_index] != Value::Text(pair.source().to_string())
+        || !translated_keys.insert(literal_tuple(&row_keys(row)))
+      {
+        continue;
+      }
+
+      // Convert the row in the same way as the database translation.
+      let converted_row = TranslateRow {
+        keys: row_keys(row),
+        locale: pair.source(),
+        texts: text_indexes.iter().map(|&i| text(&row[i])).collect(),
+        extras: extra_indexes.iter().map(|&i| row[i].clone()).collect(),
+      }
+      .convert(pair);
+      let mut new_row = row.clone();
+      new_row[locale_index] = target_locale.clone();
+      for (&i, text) in text_indexes.iter().zip(converted_row.texts) {
+        new_row[i] = text.into();
+      }
+      new_rows.push(new_row);
+    }
+  }
+
+  let translate_rows_count = filled_rows.len() + new_rows.len();
+  table.rows.extend(new_rows);
+  Ok(translate_rows_count)
+}
+
+/// Translate the table which stores one column per locale, the empty target locale columns are filled.
+fn translate_column_table(
+  target: &TranslateTarget,
+  table: &mut DumpTable,
+  pairs: &[LocalePair],
+) -> anyhow::Result<usize> {
+  let mut translate_rows_count = 0;
+  for text_column in &target.text_columns {
+    for &pair in pairs {
+      let [origin_index, target_index] = [pair.source(), pair.target()].map(|language| {
+        let column = target
+          .locale_column(text_column, language)
+          .with_context(|| format!("Table {} has no {language} locale column", target.table))?;
+        table.column_index(&column)
+      });
+      let (origin_index, target_index) = (origin_index?, target_index?);
+
+      for row in &mut table.rows {
+        if is_empty(&row[origin_index]) || !is_empty(&row[target_index]) {
+          continue;
+        }
+        let converted_row = TranslateRow {
+          keys: vec![],
+          locale: pair.source(),
+          texts: vec![text(&row[origin_index])],
+          extras: vec![],
+        }
+        .convert(pair);
+        row[target_index] = converted_row.texts[0].clone().into();
+        translate_rows_count += 1;
+      }
+    }
+  }
+  Ok(translate_rows_count)
+}
+
+/// Translate the dump table of the target in the locale pairs, return the translated rows count.
+pub fn translate_dump_table(
+  target: &TranslateTarget,
+  table: &mut DumpTable,
+  pairs: &[LocalePair],
+) -> anyhow::Result<usize> {
+  match target.locale {
+    LocaleSchema::Row(_) => translate_row_table(target, table, pairs),
+    LocaleSchema::Column(_) => translate_column_table(target, table, pairs),
+  }
+}
+
+/// Offline table translate logic, the tables in the SQL dump files are translated without the database.
+pub fn translate_dumps(
+  server_type: &ServerType,
+  dump_paths: &[PathBuf],
+  output_path: &Path,
+) -> anyhow::Result<()> {
+  info!("Run offline table translate ...");
+  let mut tables = vec![];
+  for path in dump_paths {
+    let content =
+      fs::read_to_string(path).with_context(|| format!("Read dump file {path:?} failed"))?;
+    parse_dump(&content, &mut tables)
+      .with_context(|| format!("Parse dump file {path:?} failed"))?;
+  }
+
+  let mut content =
+    format!("-- The {server_type} locale tables translated by WOW database translator.\n");
+  for translate_target in selected_targets(server_type)? {
+    let Some(table) = tables.iter_mut().find(|v| v.name == translate_target.table) else {
+      debug!(
+        "Table {} isn't in the dump files, skip it ...",
+        translate_target.table
+      );
+      continue;
+    };
+    let pairs = translate_pairs(&translate_target)?;
+    let translate_rows_count = translate_dump_table(&translate_target, table, &pairs)
+      .with_context(|| format!("Translate dump table {} failed", table.name))?;
+    info!(
+      "Translate dump table {} finished (translate rows count: {translate_rows_count}) ...",
+      table.name
+    );
+    content.push_str(&format!("\n-- {}\n{}", table.name, table.to_sql()));
+  }
+
+  fs::write(output_path, content)
+    .with_context(|| format!("Write dump file {output_path:?} failed"))?;
+  info!("The translated dump is written to {output_path:?} ...");
+
+  Ok(())
+}
+
+#[test]
+fn translate_dump_test() -> anyhow::Result<()> {
+  use crate::data::Registry;
+
+  let mut tables = vec![];
+  parse_dump(
+    r#"
+-- Dump of the AzerothCore locale table.
+DROP TABLE IF EXISTS `gameobject_template_locale`;
+CREATE TABLE `gameobject_template_locale` (
+  `entry` int unsigned NOT NULL DEFAULT '0',
+  `locale` varchar(4) NOT NULL,
+  `name` text,
+  `castBarCaption` text,
+  `VerifiedBuild` int DEFAULT NULL,
+  PRIMARY KEY (`entry`,`locale`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+/*!40000 ALTER TABLE `gameobject_template_locale` DISABLE KEYS */;
+INSERT INTO `gameobject_template_locale` VALUES (1,'zhCN','简体',NULL,-1),(1,'zhTW','繁體','',0),(2,'zhCN','It\'s ''quoted''','a;b',12340),(3,'zhCN','名字','施法',0),(3,'zhTW','名字','',0);
+INSERT INTO `locales_gameobject` (`entry`, `name_loc4`, `name_loc5`) VALUES (1, '简体', NULL), (2, '', '繁體');
+"#,
+    &mut tables,
+  )?;
+  let registry = Registry::load(&[])?;
+  let target = |server_type: ServerType, table: &str| {
+    registry
+      .targets(&server_type)
+      .into_iter()
+      .find(|v| v.table == table)
+      .unwrap()
+  };
+
+  let table = &mut tables[0];
+  assert_eq!(
+    table.columns,
+    ["entry", "locale", "name", "castBarCaption", "VerifiedBuild"]
+  );
+  assert_eq!(table.rows[0][4], Value::Int(-1));
+  assert_eq!(table.rows[2][2], Value::Text("It's 'quoted'".into()));
+  // Only the entry 2 misses the zhTW locale, and the entry 3 misses the zhTW castBarCaption.
+  let target_table = target(ServerType::AzerothCore, &table.name);
+  assert_eq!(
+    translate_dump_table(&target_table, table, &LocalePair::CHINESE)?,
+    2
+  );
+  assert_eq!(table.rows[1][3], Value::Text("".into()));
+  assert_eq!(
+    table.rows[4][3],
+    Value::Text(LocalePair::CHINESE[1].convert_text(&Some("施法".into())))
+  );
+  assert_eq!(
+    table.rows[5],
+    [
+      Value::UInt(2),
+      Value::Text("zhTW".into()),
+      Value::Text(LocalePair::CHINESE[1].convert_text(&Some("It's 'quoted'".into()))),
+      Value::Text(LocalePair::CHINESE[1].convert_text(&Some("a;b".into()))),
+      Value::UInt(12340),
+    ]
+  );
+  assert!(table.to_sql().starts_with(
+    "DELETE FROM `gameobject_template_locale`;\nINSERT INTO `gameobject_template_locale` (`entry`, `locale`, `name`, `castBarCaption`, `VerifiedBuild`) VALUES\n(1, 'zhCN', "
+  ));
+
+  let table = &mut tables[1];
+  let target_table = target(ServerType::Mangos0, &table.name);
+  assert_eq!(
+    translate_dump_table(&target_table, table, &LocalePair::CHINESE)?,
+    2
+  );
+  assert_eq!(
+    table.rows[0][2],
+    Value::Text(LocalePair::CHINESE[1].convert_text(&Some("简体".into())))
+  );
+  assert_eq!(
+    table.rows[1][1],
+    Value::Text(LocalePair::CHINESE[0].convert_text(&Some("繁體".into())))
+  );
+
+  Ok(())
+}
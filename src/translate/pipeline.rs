@@ -0,0 +1,281 @@
+use std::{
+  collections::VecDeque,
+  mem,
+  sync::atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::{bail, Context};
+use log::{debug, info, warn};
+use tokio::sync::mpsc;
+
+use crate::common::{LocalePair, StallAction, COMMAND_LINE, CONVERT_POOL};
+
+use super::{
+  overwrite_diff, translated_rows_count, worker::ConvertPool, write_in_transaction, OverwriteDiff,
+  SharedTransaction, TranslateBackend, TranslateRow, TranslateTarget, TranslateTask, Value,
+};
+
+/// The rows count of the channel between the read stage and the convert stage,
+/// the rows in flight are bounded by the channels and the stage sizes instead of the batch size.
+const CHANNEL_CAPACITY: usize = 1024;
+/// The rows count of each keyset page which is read by the read stage.
+const READ_PAGE_SIZE: usize = 1024;
+/// The rows count of each conversion job which is sent to the convert worker threads.
+const CONVERT_CHUNK_SIZE: usize = 256;
+/// The rows count of each write batch, it's independent of --batch-size so the memory doesn't grow with it.
+const WRITE_CHUNK_SIZE: usize = 1024;
+
+/// Read the untranslated rows by the keyset pages and send them to the channel. Each page is fetched completely
+/// before its rows are sent, so no result set is kept open while the later stages are busy
+/// (the server aborts the result set which isn't read within net_write_timeout).
+async fn read_stage<B: TranslateBackend>(
+  backend: &B,
+  task: &TranslateTask,
+  sender: mpsc::Sender<TranslateRow>,
+) -> anyhow::Result<()> {
+  let mut last_keys: Option<Vec<Value>> = None;
+  loop {
+    let rows = backend
+      .fetch_batch(task, last_keys.as_deref(), READ_PAGE_SIZE)
+      .await?;
+    let Some(last_row) = rows.last() else {
+      return Ok(());
+    };
+    if last_keys.as_ref() == Some(&last_row.keys) {
+      bail!("The page keys don't move forward from {:?}!", last_row.keys);
+    }
+    last_keys = Some(last_row.keys.clone());
+
+    let finished = rows.len() < READ_PAGE_SIZE;
+    for row in rows {
+      if sender.send(row).await.is_err() {
+        bail!("The convert stage stopped receiving the rows");
+      }
+    }
+    if finished {
+      return Ok(());
+    }
+  }
+}
+
+/// The rows count which passed each stage of the pipeline.
+#[derive(Default)]
+struct Progress {
+  read: AtomicUsize,
+  converted: AtomicUsize,
+  /// The rows which are sent to the write queries.
+  written: AtomicUsize,
+  /// The rows which aren't written, e.g. the unchanged rows in overwrite mode and the rows of the skipped stalled batches.
+  skipped: AtomicUsize,
+}
+
+/// Convert the read rows in the worker threads, the converted chunks are passed in the read order.
+async fn convert_stage(
+  pool: &ConvertPool,
+  pair: LocalePair,
+  mut receiver: mpsc::Receiver<TranslateRow>,
+  sender: mpsc::Sender<Vec<TranslateRow>>,
+  progress: &Progress,
+) -> anyhow::Result<()> {
+  let mut jobs = VecDeque::new();
+  let mut chunk = Vec::with_capacity(CONVERT_CHUNK_SIZE);
+  loop {
+    let row = receiver.recv().await;
+    let finished = row.is_none();
+    if let Some(row) = row {
+      progress.read.fetch_add(1, Ordering::Relaxed);
+      chunk.push(row);
+    }
+    if chunk.len() == CONVERT_CHUNK_SIZE || (finished && !chunk.is_empty()) {
+      jobs.push_back(pool.convert(pair, mem::take(&mut chunk)));
+    }
+
+    // Keep one job in flight for each worker thread, the read rows wait in the channel when all workers are busy.
+    while jobs.len() > pool.threads() || (finished && !jobs.is_empty()) {
+      let rows = jobs.pop_front().unwrap().rows().await?;
+      progress.converted.fetch_add(rows.len(), Ordering::Relaxed);
+      if sender.send(rows).await.is_err() {
+        bail!("The write stage stopped receiving the rows");
+      }
+    }
+    if finished {
+      return Ok(());
+    }
+  }
+}
+
+/// Write the converted rows in batches of the write chunk size.
+async fn write_stage<B: TranslateBackend>(
+  backend: &B,
+  task: &TranslateTask,
+  transaction: &mut SharedTransaction,
+  mut receiver: mpsc::Receiver<Vec<TranslateRow>>,
+  progress: &Progress,
+  diff: &mut OverwriteDiff,
+) -> anyhow::Result<u64> {
+  let TranslateTarget {
+    database, table, ..
+  } = backend.target();
+  let mut batch = Vec::with_capacity(WRITE_CHUNK_SIZE);
+  let mut translate_rows_count = 0;
+
+  loop {
+    let rows = receiver.recv().await;
+    let finished = rows.is_none();
+    batch.extend(rows.into_iter().flatten());
+
+    while batch.len() >= WRITE_CHUNK_SIZE || (finished && !batch.is_empty()) {
+      let mut rows: Vec<_> = batch.drain(..batch.len().min(WRITE_CHUNK_SIZE)).collect();
+      let batch_rows_count = rows.len();
+      let (first_keys, last_keys) = (
+        rows[0].keys.clone(),
+        rows[batch_rows_count - 1].keys.clone(),
+      );
+      if task.overwrite {
+        rows = overwrite_diff(backend, task, rows, diff).await?;
+      }
+      let write_rows_count = rows.len();
+      let rows_affected = if rows.is_empty() {
+        0
+      } else {
+        write_in_transaction(backend, transaction, task, rows).await?
+      };
+      translate_rows_count += translated_rows_count(task, write_rows_count, rows_affected);
+
+      // The written rows are all untranslated (or changed in overwrite mode) when they are fetched,
+      // so a batch which affects none of them is stalled, e.g. the AzerothCore rows which are all ignored by INSERT IGNORE
+      // or the MaNGOS rows which are all unchanged by UPDATE.
+      let mut written_rows_count = write_rows_count;
+      if rows_affected == 0 && write_rows_count > 0 {
+        let message = format!("Translate table {database}.{table} {:?} batch {first_keys:?} ~ {last_keys:?} made no progress, none of the {write_rows_count} rows are written", task.text_columns);
+        match COMMAND_LINE.on_stall {
+          StallAction::Abort => bail!("{message}!"),
+          StallAction::Skip => warn!("{message}, skip the batch ..."),
+        }
+        written_rows_count = 0;
+      }
+      progress
+        .written
+        .fetch_add(written_rows_count, Ordering::Relaxed);
+      progress
+        .skipped
+        .fetch_add(batch_rows_count - written_rows_count, Ordering::Relaxed);
+
+      // Log the progress of each stage and row affects.
+      info!(
+        "{database}.{table} Progress: read {}, converted {}, written {}, skipped {}/{}",
+        progress.read.load(Ordering::Relaxed),
+        progress.converted.load(Ordering::Relaxed),
+        progress.written.load(Ordering::Relaxed),
+        progress.skipped.load(Ordering::Relaxed),
+        task.count
+      );
+      debug!("{database}.{table} Rows affected: {rows_affected}");
+    }
+    if finished {
+      return Ok(translate_rows_count);
+    }
+  }
+}
+
+/// Translate the task by the streaming pipeline: the read stage reads the untranslated rows page by page,
+/// the convert stage converts them in the worker threads and the write stage writes them in batches.
+/// The stages run concurrently with the bounded channels as the backpressure,
+/// the first failed stage stops the pipeline and the other stages are dropped.
+pub(super) async fn translate_pipeline<B: TranslateBackend>(
+  backend: &B,
+  task: &TranslateTask,
+  transaction: &mut SharedTransaction,
+  diff: &mut OverwriteDiff,
+) -> anyhow::Result<u64> {
+  let TranslateTarget {
+    database, table, ..
+  } = backend.target();
+  let text_columns = &task.text_columns;
+  let (read_sender, read_receiver) = mpsc::channel(CHANNEL_CAPACITY);
+  let (convert_sender, convert_receiver) = mpsc::channel(CHANNEL_CAPACITY / CONVERT_CHUNK_SIZE);
+  let progress = Progress::default();
+
+  let read = async {
+    read_stage(backend, task, read_sender)
+      .await
+      .with_context(|| format!("Read stage of table {database}.{table} {text_columns:?} failed"))
+  };
+  let convert = async {
+    convert_stage(
+      &CONVERT_POOL,
+      task.pair,
+      read_receiver,
+      convert_sender,
+      &progress,
+    )
+    .await
+    .with_context(|| format!("Convert stage of table {database}.{table} {text_columns:?} failed"))
+  };
+  let write = async {
+    write_stage(
+      backend,
+      task,
+      transaction,
+      convert_receiver,
+      &progress,
+      diff,
+    )
+    .await
+    .with_context(|| format!("Write stage of table {database}.{table} {text_columns:?} failed"))
+  };
+
+  let (_, _, translate_rows_count) = tokio::try_join!(read, convert, write)?;
+  Ok(translate_rows_count)
+}
+
+#[tokio::test]
+async fn convert_stage_test() -> anyhow::Result<()> {
+  use crate::common::Language;
+
+  let pool = ConvertPool::new(2);
+  let progress = Progress::default();
+  let (read_sender, read_receiver) = mpsc::channel(CHANNEL_CAPACITY);
+  let (convert_sender, mut convert_receiver) = mpsc::channel(1);
+  let rows_count = CONVERT_CHUNK_SIZE as u64 * 5 + 1;
+
+  let read = async move {
+    for v in 0..rows_count {
+      let row = TranslateRow {
+        keys: vec![Value::UInt(v)],
+        locale: Language::Chinese,
+        texts: vec![Some(v.to_string())],
+        extras: vec![],
+      };
+      read_sender.send(row).await?;
+    }
+    anyhow::Ok(())
+  };
+  let convert = convert_stage(
+    &pool,
+    LocalePair::CHINESE[1],
+    read_receiver,
+    convert_sender,
+    &progress,
+  );
+  let write = async {
+    let mut keys = vec![];
+    while let Some(rows) = convert_receiver.recv().await {
+      assert!(rows.len() <= CONVERT_CHUNK_SIZE);
+      assert!(rows.iter().all(|v| v.locale == Language::Taiwanese));
+      keys.extend(rows.into_iter().flat_map(|v| v.keys));
+    }
+    anyhow::Ok(keys)
+  };
+  let (_, _, keys) = tokio::try_join!(read, convert, write)?;
+
+  // The converted rows keep the read order.
+  assert_eq!(keys, (0..rows_count).map(Value::UInt).collect::<Vec<_>>());
+  assert_eq!(progress.read.load(Ordering::Relaxed), rows_count as usize);
+  assert_eq!(
+    progress.converted.load(Ordering::Relaxed),
+    rows_count as usize
+  );
+
+  Ok(())
+}
@@ -0,0 +1,369 @@
+use std::{collections::HashMap, fs};
+
+use anyhow::{bail, Context};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::common::{
+  selected_targets, LocalePair, ServerType, TransactionScope, COMMAND_LINE, POOL,
+};
+
+use super::{
+  check_translation, commit, translated_rows_count, with_backend, write_in_transaction,
+  SharedTransaction, TranslateBackend, TranslateRow, TranslateTarget, TranslateTask, Value,
+};
+
+/// The reviewable translate plan, it contains every row which would be written by the translation.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TranslatePlan {
+  pub tables: Vec<TablePlan>,
+}
+
+impl TranslatePlan {
+  pub fn rows_count(&self) -> usize {
+    self
+      .tables
+      .iter()
+      .flat_map(|v| &v.tasks)
+      .map(|v| v.rows.len())
+      .sum()
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TablePlan {
+  pub target: TranslateTarget,
+  pub tasks: Vec<TaskPlan>,
+}
+
+/// The planned rows of the text columns which are translated together from the source locale to the target locale.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskPlan {
+  pub pair: LocalePair,
+  pub text_columns: Vec<String>,
+  /// Whether the existing target locale texts are overwritten by the planned rows.
+  #[serde(default)]
+  pub overwrite: bool,
+  /// Whether the planned rows fill the empty text columns of the existing target locale rows.
+  #[serde(default)]
+  pub fill: bool,
+  pub rows: Vec<PlanRow>,
+}
+
+impl TaskPlan {
+  /// Build the task of the plan, the locale pair in the plan file is validated when it's loaded.
+  pub(super) fn task(&self) -> TranslateTask {
+    TranslateTask {
+      pair: self.pair,
+      text_columns: self.text_columns.clone(),
+      count: self.rows.len() as i64,
+      column_counts: vec![self.rows.len() as i64; self.text_columns.len()],
+      overwrite: self.overwrite,
+      fill: self.fill,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlanRow {
+  pub keys: Vec<Value>,
+  pub texts: Vec<PlanText>,
+  pub extras: Vec<Value>,
+}
+
+/// The source text of the column and the converted text which would be written.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlanText {
+  pub column: String,
+  pub source: Option<String>,
+  pub converted: Option<String>,
+}
+
+impl PlanRow {
+  fn new(text_columns: &[String], row: &TranslateRow, pair: LocalePair) -> Self {
+    let converted_row = row.convert(pair);
+    PlanRow {
+      keys: row.keys.clone(),
+      texts: text_columns
+        .iter()
+        .zip(&row.texts)
+        .zip(converted_row.texts)
+        .map(|((column, source), converted)| PlanText {
+          column: column.clone(),
+          source: source.clone(),
+          converted,
+        })
+        .collect(),
+      extras: row.extras.clone(),
+    }
+  }
+
+  /// The source row in the source locale, it's compared with the current database row.
+  fn source_row(&self, pair: LocalePair) -> TranslateRow {
+    TranslateRow {
+      keys: self.keys.clone(),
+      locale: pair.source(),
+      texts: self.texts.iter().map(|v| v.source.clone()).collect(),
+      extras: self.extras.clone(),
+    }
+  }
+
+  /// The converted row in the target locale, it's written exactly as planned.
+  pub(super) fn converted_row(&self, pair: LocalePair) -> TranslateRow {
+    TranslateRow {
+      keys: self.keys.clone(),
+      locale: pair.target(),
+      texts: self.texts.iter().map(|v| v.converted.clone()).collect(),
+      extras: self.extras.clone(),
+    }
+  }
+}
+
+async fn plan_task<B: TranslateBackend>(
+  backend: &B,
+  task: TranslateTask,
+) -> anyhow::Result<TaskPlan> {
+  let TranslateTarget {
+    database, table, ..
+  } = backend.target();
+  let mut rows = vec![];
+  let mut last_keys: Option<Vec<Value>> = None;
+
+  loop {
+    let batch_rows = backend
+      .fetch_batch(&task, last_keys.as_deref(), COMMAND_LINE.batch_size)
+      .await?;
+    let Some(last_row) = batch_rows.last() else {
+      break;
+    };
+    if last_keys.as_ref() == Some(&last_row.keys) {
+      bail!(
+        "Plan table {database}.{table} {:?} stalled, the batch keys don't move forward from {:?}!",
+        task.text_columns,
+        last_row.keys
+      );
+    }
+    last_keys = Some(last_row.keys.clone());
+    rows.extend(
+      batch_rows
+        .iter()
+        .map(|v| PlanRow::new(&task.text_columns, v, task.pair)),
+    );
+  }
+
+  info!(
+    "Plan table {database}.{table} {:?} {} finished (planned rows count: {}) ...",
+    task.text_columns,
+    task.pair,
+    rows.len()
+  );
+  Ok(TaskPlan {
+    pair: task.pair,
+    text_columns: task.text_columns,
+    overwrite: task.overwrite,
+    fill: task.fill,
+    rows,
+  })
+}
+
+pub(super) async fn plan_table<B: TranslateBackend>(backend: &B) -> anyhow::Result<TablePlan> {
+  let mut tasks = vec![];
+  for task in check_translation(backend).await? {
+    tasks.push(plan_task(backend, task).await?);
+  }
+  Ok(TablePlan {
+    target: backend.target().clone(),
+    tasks,
+  })
+}
+
+/// Compare the planned source rows with the current database rows, return the changed rows count.
+async fn verify_table<B: TranslateBackend>(
+  backend: &B,
+  table_plan: &TablePlan,
+) -> anyhow::Result<usize> {
+  let TranslateTarget {
+    database, table, ..
+  } = backend.target();
+  let mut changed_rows_count = 0;
+
+  for task_plan in &table_plan.tasks {
+    let task = task_plan.task();
+    for rows in task_plan.rows.chunks(COMMAND_LINE.batch_size) {
+      let keys: Vec<_> = rows.iter().map(|v| v.keys.clone()).collect();
+      let current_rows = backend.fetch_rows(&task, &keys).await?;
+      let current_rows: HashMap<_, _> = current_rows
+        .iter()
+        .map(|v| (v.keys.as_slice(), v))
+        .collect();
+      for row in rows {
+        let source_row = row.source_row(task.pair);
+        let unchanged = current_rows
+          .get(source_row.keys.as_slice())
+          .is_some_and(|v| v.texts == source_row.texts && v.extras == source_row.extras);
+        if !unchanged {
+          warn!(
+            "Table {database}.{table} {:?} row {:?} changed since the plan was made",
+            task.text_columns, row.keys
+          );
+          changed_rows_count += 1;
+        }
+      }
+    }
+  }
+
+  Ok(changed_rows_count)
+}
+
+async fn apply_task<B: TranslateBackend>(
+  backend: &B,
+  task_plan: &TaskPlan,
+  transaction: &mut SharedTransaction,
+) -> anyhow::Result<()> {
+  let TranslateTarget {
+    database, table, ..
+  } = backend.target();
+  let task = task_plan.task();
+  let mut translate_rows_count = 0;
+
+  for rows in task_plan.rows.chunks(COMMAND_LINE.batch_size) {
+    let rows: Vec<_> = rows.iter().map(|v| v.converted_row(task.pair)).collect();
+    let write_rows_count = rows.len();
+    let rows_affected = write_in_transaction(backend, transaction, &task, rows).await?;
+    translate_rows_count += translated_rows_count(&task, write_rows_count, rows_affected);
+  }
+
+  info!(
+    "Apply table {database}.{table} {:?} {} finished (translate rows count: {translate_rows_count}/{}) ...",
+    task.text_columns, task.pair, task.count
+  );
+  Ok(())
+}
+
+async fn apply_table<B: TranslateBackend>(
+  backend: &B,
+  table_plan: &TablePlan,
+  transaction: &mut SharedTransaction,
+) -> anyhow::Result<()> {
+  if let (TransactionScope::Table, None) = (COMMAND_LINE.transaction, &transaction) {
+    let mut table_transaction = Some(POOL.begin().await?);
+    for task_plan in &table_plan.tasks {
+      apply_task(backend, task_plan, &mut table_transaction).await?;
+    }
+    commit(table_transaction).await?;
+  } else {
+    for task_plan in &table_plan.tasks {
+      apply_task(backend, task_plan, transaction).await?;
+    }
+  }
+  Ok(())
+}
+
+/// Table translate plan logic, the rows which would be translated are written to the plan file.
+pub async fn write_plan(server_type: &ServerType) -> anyhow::Result<()> {
+  info!("Plan table translate ...");
+  let mut plan = TranslatePlan::default();
+
+  for translate_target in selected_targets(server_type)? {
+    let table_plan = with_backend!(translate_target, v => plan_table(v).await)?;
+    if !table_plan.tasks.is_empty() {
+      plan.tables.push(table_plan);
+    }
+  }
+
+  let path = &COMMAND_LINE.plan_file;
+  fs::write(path, serde_json::to_string_pretty(&plan)?)
+    .with_context(|| format!("Write plan file {path:?} failed"))?;
+  info!(
+    "The translate plan ({} rows) is written to {path:?}, review it and then apply it ...",
+    plan.rows_count()
+  );
+
+  Ok(())
+}
+
+/// Translate plan apply logic, the plan is refused when any planned source row has changed.
+pub async fn apply_plan() -> anyhow::Result<()> {
+  let path = &COMMAND_LINE.plan_file;
+  let content =
+    fs::read_to_string(path).with_context(|| format!("Read plan file {path:?} failed"))?;
+  let plan: TranslatePlan =
+    serde_json::from_str(&content).with_context(|| format!("Parse plan file {path:?} failed"))?;
+
+  info!("Verify the translate plan source rows ...");
+  let mut changed_rows_count = 0;
+  for table_plan in &plan.tables {
+    changed_rows_count += with_backend!(table_plan.target, v => verify_table(v, table_plan).await)?;
+  }
+  if changed_rows_count > 0 {
+    bail!(
+      "{changed_rows_count} source rows changed since the plan was made, refuse to apply the plan!"
+    );
+  }
+
+  info!("Apply the translate plan ({} rows) ...", plan.rows_count());
+  let mut transaction = match COMMAND_LINE.transaction {
+    TransactionScope::Run => Some(POOL.begin().await?),
+    TransactionScope::Batch | TransactionScope::Table => None,
+  };
+  for table_plan in &plan.tables {
+    with_backend!(table_plan.target, v => apply_table(v, table_plan, &mut transaction).await)?;
+  }
+  commit(transaction).await?;
+
+  Ok(())
+}
+
+#[test]
+fn plan_row_test() -> anyhow::Result<()> {
+  use super::{Language, LocaleSchema};
+
+  let row = TranslateRow {
+    keys: vec![Value::UInt(1)],
+    locale: Language::Chinese,
+    texts: vec![Some("简体中文".into()), None],
+    extras: vec![Value::Int(12340)],
+  };
+  let text_columns = ["Title".to_string(), "Details".to_string()];
+  let pair = LocalePair::CHINESE[1];
+  let plan_row = PlanRow::new(&text_columns, &row, pair);
+  assert_eq!(plan_row.texts[0].column, "Title");
+  assert_eq!(plan_row.texts[0].source, row.texts[0]);
+  assert_eq!(plan_row.texts[1].converted.as_deref(), Some(""));
+
+  // The plan file must keep the exact values to compare and write the rows.
+  let plan = TranslatePlan {
+    tables: vec![TablePlan {
+      target: TranslateTarget {
+        database: "acore_world".into(),
+        table: "quest_template_locale".into(),
+        key_columns: vec!["ID".into()],
+        locale: LocaleSchema::Row("locale".into()),
+        text_columns: text_columns.to_vec(),
+        extra_columns: vec!["VerifiedBuild".into()],
+        policy: Default::default(),
+        filter: vec![],
+      },
+      tasks: vec![TaskPlan {
+        pair,
+        text_columns: text_columns.to_vec(),
+        overwrite: false,
+        fill: false,
+        rows: vec![plan_row],
+      }],
+    }],
+  };
+  let plan: TranslatePlan = serde_json::from_str(&serde_json::to_string_pretty(&plan)?)?;
+  assert_eq!(plan.rows_count(), 1);
+  let plan_row = &plan.tables[0].tasks[0].rows[0];
+  let source_row = plan_row.source_row(pair);
+  assert_eq!(source_row.keys, row.keys);
+  assert_eq!(source_row.texts, row.texts);
+  assert_eq!(source_row.extras, row.extras);
+  assert_eq!(plan_row.converted_row(pair).texts, row.convert(pair).texts);
+  assert_eq!(plan.tables[0].tasks[0].task().pair, pair);
+  // The unsupported locale pair of the plan file is rejected when it's loaded.
+  assert!(serde_json::from_str::<LocalePair>(r#"{"source":"enUS","target":"zhTW"}"#).is_err());
+
+  Ok(())
+}
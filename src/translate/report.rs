@@ -0,0 +1,288 @@
+use std::{fs, path::Path};
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::common::{Language, ReportFormat, TranslatePolicy};
+
+use super::{diff::DiffKind, literal_tuple, TranslateTarget, TranslateTask, Value};
+
+/// The process exit code when the check finds untranslated contents, it's distinct from the error exit code 1.
+pub const UNTRANSLATED_EXIT_CODE: i32 = 2;
+
+/// The untranslated rows count of the text column from the origin language to the target language.
+#[derive(Clone, Debug, Serialize)]
+pub struct ColumnCheck {
+  pub column: String,
+  pub origin_language: Language,
+  pub target_language: Language,
+  pub untranslated: i64,
+  /// Whether the target locale rows exist but the column is empty.
+  pub fill: bool,
+  /// The keys of the untranslated rows as SQL row constructors, e.g. `(1, 2)`, they're empty in overwrite mode.
+  pub missing_keys: Vec<String>,
+}
+
+/// The count of the rows which have the texts of the locale.
+#[derive(Clone, Debug, Serialize)]
+pub struct LocaleCoverage {
+  pub locale: Language,
+  pub rows: i64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TableCheck {
+  pub database: String,
+  pub table: String,
+  /// The source of truth policy, only the untranslated columns in the allowed directions are checked.
+  pub policy: TranslatePolicy,
+  pub coverage: Vec<LocaleCoverage>,
+  pub columns: Vec<ColumnCheck>,
+}
+
+impl TableCheck {
+  /// Build the table check of the tasks, each task has the keys of its untranslated rows.
+  pub fn new(
+    target: &TranslateTarget,
+    coverage: Vec<LocaleCoverage>,
+    tasks: &[(TranslateTask, Vec<Vec<Value>>)],
+  ) -> Self {
+    TableCheck {
+      database: target.database.clone(),
+      table: target.table.clone(),
+      policy: target.policy,
+      coverage,
+      columns: tasks
+        .iter()
+        .flat_map(|(task, keys)| {
+          let missing_keys: Vec<_> = keys.iter().map(|v| literal_tuple(v)).collect();
+          task.text_columns.iter().map(move |column| ColumnCheck {
+            column: column.clone(),
+            origin_language: task.pair.source(),
+            target_language: task.pair.target(),
+            untranslated: task.count,
+            fill: task.fill,
+            missing_keys: missing_keys.clone(),
+          })
+        })
+        .collect(),
+    }
+  }
+
+  pub fn is_translated(&self) -> bool {
+    self.columns.is_empty()
+  }
+}
+
+/// The translation check report of all the checked tables.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CheckReport {
+  pub tables: Vec<TableCheck>,
+}
+
+/// Quote the CSV field when it contains the special characters.
+fn csv_field(field: &str) -> String {
+  if field.contains([',', '"', '\n', '\r']) {
+    format!("\"{}\"", field.replace('"', "\"\""))
+  } else {
+    field.to_string()
+  }
+}
+
+impl CheckReport {
+  pub fn untranslated_tables(&self) -> Vec<&str> {
+    self
+      .tables
+      .iter()
+      .filter(|v| !v.is_translated())
+      .map(|v| v.table.as_str())
+      .collect()
+  }
+
+  pub fn has_untranslated(&self) -> bool {
+    self.tables.iter().any(|v| !v.is_translated())
+  }
+
+  /// Build the CSV report, each record is an untranslated column of the table,
+  /// the missing keys are separated by spaces.
+  pub fn to_csv(&self) -> String {
+    let mut csv = String::from(
+      "database,table,column,origin_language,target_language,untranslated,fill,missing_keys\n",
+    );
+    for table in &self.tables {
+      for column in &table.columns {
+        let fields = [
+          csv_field(&table.database),
+          csv_field(&table.table),
+          csv_field(&column.column),
+          column.origin_language.to_string(),
+          column.target_language.to_string(),
+          column.untranslated.to_string(),
+          column.fill.to_string(),
+          csv_field(&column.missing_keys.join(" ")),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+      }
+    }
+    csv
+  }
+
+  pub fn write(&self, path: &Path, format: ReportFormat) -> anyhow::Result<()> {
+    let content = match format {
+      ReportFormat::Json => serde_json::to_string_pretty(self)?,
+      ReportFormat::Csv => self.to_csv(),
+    };
+    fs::write(path, content).with_context(|| format!("Write check report {path:?} failed"))
+  }
+}
+
+/// The row whose converted text isn't identical to the stored text of the other locale.
+#[derive(Clone, Debug, Serialize)]
+pub struct DiffRow {
+  pub database: String,
+  pub table: String,
+  pub column: String,
+  pub origin_language: Language,
+  pub target_language: Language,
+  /// The keys of the row as SQL row constructor, e.g. `(1, 2)`.
+  pub keys: String,
+  pub origin_text: String,
+  /// The origin text converted to the target language.
+  pub converted_text: String,
+  pub stored_text: String,
+}
+
+/// The consistency diff report of all the diffed tables, the rows are grouped by the diff kind.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DiffReport {
+  pub variant: Vec<DiffRow>,
+  pub different: Vec<DiffRow>,
+}
+
+impl DiffReport {
+  /// Add the row to the group of the kind, the identical rows aren't reported.
+  pub fn push(&mut self, kind: DiffKind, row: DiffRow) {
+    match kind {
+      DiffKind::Identical => {}
+      DiffKind::Variant => self.variant.push(row),
+      DiffKind::Different => self.different.push(row),
+    }
+  }
+
+  pub fn merge(&mut self, other: Self) {
+    self.variant.extend(other.variant);
+    self.different.extend(other.different);
+  }
+
+  /// Build the CSV report, each record is a variant or different row of the table column.
+  pub fn to_csv(&self) -> String {
+    let mut csv = String::from(
+      "kind,database,table,column,origin_language,target_language,keys,origin_text,converted_text,stored_text\n",
+    );
+    for (kind, rows) in [("variant", &self.variant), ("different", &self.different)] {
+      for row in rows {
+        let fields = [
+          kind.to_string(),
+          csv_field(&row.database),
+          csv_field(&row.table),
+          csv_field(&row.column),
+          row.origin_language.to_string(),
+          row.target_language.to_string(),
+          csv_field(&row.keys),
+          csv_field(&row.origin_text),
+          csv_field(&row.converted_text),
+          csv_field(&row.stored_text),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+      }
+    }
+    csv
+  }
+
+  pub fn write(&self, path: &Path, format: ReportFormat) -> anyhow::Result<()> {
+    let content = match format {
+      ReportFormat::Json => serde_json::to_string_pretty(self)?,
+      ReportFormat::Csv => self.to_csv(),
+    };
+    fs::write(path, content).with_context(|| format!("Write diff report {path:?} failed"))
+  }
+}
+
+#[test]
+fn check_report_test() -> anyhow::Result<()> {
+  use super::{test_target, test_task};
+  use crate::common::ServerType;
+
+  let task = test_task(&["name"], 3);
+  let coverage = vec![LocaleCoverage {
+    locale: Language::Chinese,
+    rows: 10,
+  }];
+  let keys = vec![
+    vec![Value::UInt(1)],
+    vec![Value::UInt(2)],
+    vec![Value::UInt(5)],
+  ];
+  let report = CheckReport {
+    tables: vec![
+      TableCheck::new(
+        &test_target(&ServerType::Mangos0, "locales_gameobject"),
+        coverage.clone(),
+        &[(task, keys)],
+      ),
+      TableCheck::new(
+        &test_target(&ServerType::Mangos0, "locales_points_of_interest"),
+        coverage,
+        &[],
+      ),
+    ],
+  };
+
+  assert!(report.has_untranslated());
+  assert_eq!(report.untranslated_tables(), ["locales_gameobject"]);
+  assert_eq!(
+    report.to_csv(),
+    "database,table,column,origin_language,target_language,untranslated,fill,missing_keys\nmangos0,locales_gameobject,name,zhCN,zhTW,3,false,(1) (2) (5)\n"
+  );
+  let json: serde_json::Value = serde_json::to_value(&report)?;
+  assert_eq!(json["tables"][0]["columns"][0]["origin_language"], "zhCN");
+  assert_eq!(json["tables"][0]["coverage"][0]["locale"], "zhCN");
+  assert_eq!(json["tables"][0]["policy"], "either");
+  assert_eq!(json["tables"][0]["columns"][0]["missing_keys"][2], "(5)");
+  assert_eq!(
+    json["tables"][1]["columns"].as_array().map(Vec::len),
+    Some(0)
+  );
+  assert_eq!(csv_field("a,\"b\""), "\"a,\"\"b\"\"\"");
+
+  Ok(())
+}
+
+#[test]
+fn diff_report_test() {
+  let row = |column: &str, stored_text: &str| DiffRow {
+    database: "acore_world".into(),
+    table: "quest_template_locale".into(),
+    column: column.into(),
+    origin_language: Language::Chinese,
+    target_language: Language::Taiwanese,
+    keys: "(1, 'zhCN')".into(),
+    origin_text: "你好，世界".into(),
+    converted_text: "你好，世界".into(),
+    stored_text: stored_text.into(),
+  };
+  let mut report = DiffReport::default();
+  report.push(DiffKind::Different, row("Details", "再見"));
+  report.push(DiffKind::Identical, row("Title", "你好，世界"));
+  report.push(DiffKind::Variant, row("Title", "你好, 世界"));
+
+  // The rows are grouped by the kind and the identical rows aren't reported.
+  assert_eq!(
+    report.to_csv(),
+    "kind,database,table,column,origin_language,target_language,keys,origin_text,converted_text,stored_text\n\
+     variant,acore_world,quest_template_locale,Title,zhCN,zhTW,\"(1, 'zhCN')\",你好，世界,你好，世界,\"你好, 世界\"\n\
+     different,acore_world,quest_template_locale,Details,zhCN,zhTW,\"(1, 'zhCN')\",你好，世界,你好，世界,再見\n"
+  );
+}
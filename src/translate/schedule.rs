@@ -0,0 +1,158 @@
+use std::future::Future;
+
+use log::{info, warn};
+use sqlx::{MySql, QueryBuilder};
+use tokio::task::JoinSet;
+
+use crate::common::POOL;
+
+use super::{
+  filter::{Condition, Operator},
+  quote, TranslateTarget, Value,
+};
+
+/// Run the job of each target with at most `jobs` jobs at once, the results keep the order of the targets.
+/// The first failed job stops the schedule and the running jobs are aborted.
+pub async fn schedule<T, F, Fut>(
+  targets: Vec<TranslateTarget>,
+  jobs: usize,
+  job: F,
+) -> anyhow::Result<Vec<T>>
+where
+  T: Send + 'static,
+  F: Fn(TranslateTarget) -> Fut,
+  Fut: Future<Output = anyhow::Result<T>> + Send + 'static,
+{
+  let mut results: Vec<Option<T>> = targets.iter().map(|_| None).collect();
+  let mut targets = targets.into_iter().enumerate();
+  let mut join_set = JoinSet::new();
+
+  loop {
+    while join_set.len() < jobs.max(1) {
+      let Some((index, target)) = targets.next() else {
+        break;
+      };
+      let future = job(target);
+      join_set.spawn(async move { anyhow::Ok((index, future.await?)) });
+    }
+    let Some(result) = join_set.join_next().await else {
+      break;
+    };
+    let (index, value) = result??;
+    results[index] = Some(value);
+  }
+
+  Ok(results.into_iter().flatten().collect())
+}
+
+/// Split the inclusive integer range into at most `count` inclusive ranges of the same size.
+fn key_ranges(min: i128, max: i128, count: usize) -> Vec<(i128, i128)> {
+  let count = count as i128;
+  let step = ((max - min + count) / count).max(1);
+  (0..count)
+    .map(|i| (min + i * step, (min + (i + 1) * step - 1).min(max)))
+    .take_while(|(start, _)| *start <= max)
+    .collect()
+}
+
+fn int_value(value: i128) -> Value {
+  i64::try_from(value).map_or(Value::UInt(value as u64), Value::Int)
+}
+
+/// Split the target into the key ranges of the first key column, each key range is a target with the BETWEEN filter,
+/// so the ranges of a huge table can be translated as separate jobs. Only the integer keys can be split.
+pub async fn split_key_ranges(
+  target: TranslateTarget,
+  count: usize,
+) -> anyhow::Result<Vec<TranslateTarget>> {
+  if count <= 1 {
+    return Ok(vec![target]);
+  }
+  let TranslateTarget {
+    database,
+    table,
+    key_columns,
+    ..
+  } = &target;
+  let key_column = &key_columns[0];
+
+  let mut builder = QueryBuilder::<MySql>::new(format!(
+    "SELECT min({0}) AS `min`, max({0}) AS `max` FROM {1}.{2} WHERE 1",
+    quote(key_column),
+    quote(database),
+    quote(table)
+  ));
+  target.push_filter(&mut builder, None);
+  let row = builder.build().fetch_one(&*POOL).await?;
+  let (min, max) = match (Value::decode(&row, "min")?, Value::decode(&row, "max")?) {
+    (Value::Int(min), Value::Int(max)) => (min as i128, max as i128),
+    (Value::UInt(min), Value::UInt(max)) => (min as i128, max as i128),
+    (Value::Null, Value::Null) => return Ok(vec![target]),
+    _ => {
+      warn!("Table {database}.{table} key column {key_column} isn't an integer, it can't be split into key ranges ...");
+      return Ok(vec![target]);
+    }
+  };
+
+  let ranges = key_ranges(min, max, count);
+  info!(
+    "Split table {database}.{table} into {} key ranges of {key_column} {min} ~ {max} ...",
+    ranges.len()
+  );
+  Ok(
+    ranges
+      .into_iter()
+      .map(|(start, end)| {
+        let mut range_target = target.clone();
+        range_target.filter.push(Condition {
+          column: key_column.clone(),
+          operator: Operator::Between,
+          values: vec![int_value(start), int_value(end)],
+        });
+        range_target
+      })
+      .collect(),
+  )
+}
+
+#[tokio::test]
+async fn schedule_test() -> anyhow::Result<()> {
+  use std::{
+    sync::{
+      atomic::{AtomicUsize, Ordering},
+      Arc,
+    },
+    time::Duration,
+  };
+
+  use crate::{common::ServerType, data::Registry};
+
+  assert_eq!(key_ranges(1, 10, 3), [(1, 4), (5, 8), (9, 10)]);
+  assert_eq!(key_ranges(-2, 1, 8), [(-2, -2), (-1, -1), (0, 0), (1, 1)]);
+  assert_eq!(key_ranges(5, 5, 4), [(5, 5)]);
+  assert_eq!(int_value(u64::MAX as i128), Value::UInt(u64::MAX));
+  assert_eq!(int_value(-1), Value::Int(-1));
+
+  let targets = Registry::load(&[])?.targets(&ServerType::AzerothCore);
+  let tables: Vec<_> = targets.iter().map(|v| v.table.clone()).collect();
+  let (running, max_running) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+  let results = schedule(targets, 3, |target| {
+    let (running, max_running) = (running.clone(), max_running.clone());
+    async move {
+      let count = running.fetch_add(1, Ordering::SeqCst) + 1;
+      max_running.fetch_max(count, Ordering::SeqCst);
+      tokio::time::sleep(Duration::from_millis(10)).await;
+      running.fetch_sub(1, Ordering::SeqCst);
+      anyhow::Ok(target.table)
+    }
+  })
+  .await?;
+  // The results keep the order of the targets.
+  assert_eq!(results, tables);
+  assert_eq!(max_running.load(Ordering::SeqCst), 3);
+
+  let results = schedule(vec![], 3, |_| async { anyhow::Ok(()) }).await?;
+  assert!(results.is_empty());
+
+  Ok(())
+}
@@ -0,0 +1,155 @@
+use std::{
+  panic::{self, AssertUnwindSafe},
+  sync::{mpsc, Arc, Mutex},
+  thread,
+};
+
+use anyhow::Context;
+use tokio::sync::oneshot;
+
+use crate::common::LocalePair;
+
+use super::TranslateRow;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// The pool of the worker threads which convert the whole batches,
+/// so the CPU bound conversion doesn't block the async tasks which read and write the database.
+pub struct ConvertPool {
+  sender: Mutex<mpsc::Sender<Job>>,
+  threads: usize,
+}
+
+/// The batch which is converting in the worker threads.
+pub struct ConvertJob(oneshot::Receiver<Vec<TranslateRow>>);
+
+impl ConvertJob {
+  /// Wait the converted rows of the batch.
+  pub async fn rows(self) -> anyhow::Result<Vec<TranslateRow>> {
+    self
+      .0
+      .await
+      .context("The convert worker thread failed to convert the batch")
+  }
+}
+
+impl ConvertPool {
+  pub fn new(threads: usize) -> Self {
+    let (sender, receiver) = mpsc::channel::<Job>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    for index in 0..threads {
+      let receiver = receiver.clone();
+      thread::Builder::new()
+        .name(format!("convert-worker-{index}"))
+        .spawn(move || {
+          loop {
+            // Receive the job in its own statement, so the lock is released before the job runs
+            // and the other workers can receive the next job.
+            let job = receiver.lock().unwrap().recv();
+            let Ok(job) = job else {
+              break;
+            };
+            // The panic job drops its result sender, the panic is reported by the job receiver.
+            let _ = panic::catch_unwind(AssertUnwindSafe(job));
+          }
+        })
+        .expect("Spawn convert worker thread error!");
+    }
+    ConvertPool {
+      sender: Mutex::new(sender),
+      threads,
+    }
+  }
+
+  /// The worker threads count of the pool.
+  pub fn threads(&self) -> usize {
+    self.threads
+  }
+
+  /// Send the batch rows to the worker threads, the rows are converted while the caller does the database I/O.
+  pub fn convert(&self, pair: LocalePair, rows: Vec<TranslateRow>) -> ConvertJob {
+    let (sender, receiver) = oneshot::channel();
+    let job: Job = Box::new(move || {
+      let _ = sender.send(rows.iter().map(|v| v.convert(pair)).collect());
+    });
+    // The workers never exit, the dropped job is reported by the job receiver anyway.
+    let _ = self.sender.lock().unwrap().send(job);
+    ConvertJob(receiver)
+  }
+}
+
+#[tokio::test]
+async fn convert_pool_test() -> anyhow::Result<()> {
+  use super::Value;
+  use crate::common::Language;
+
+  let pool = ConvertPool::new(2);
+  let pair = LocalePair::CHINESE[1];
+  let jobs: Vec<_> = (0..4)
+    .map(|v| {
+      let rows = (0..100)
+        .map(|i| TranslateRow {
+          keys: vec![Value::UInt(v * 100 + i)],
+          locale: Language::Chinese,
+          texts: vec![Some(format!("$N{i}")), None],
+          extras: vec![],
+        })
+        .collect();
+      pool.convert(pair, rows)
+    })
+    .collect();
+
+  for (v, job) in (0..4).zip(jobs) {
+    let rows = job.rows().await?;
+    assert_eq!(rows.len(), 100);
+    assert_eq!(rows[1].keys, [Value::UInt(v * 100 + 1)]);
+    assert_eq!(rows[1].locale, Language::Taiwanese);
+    assert_eq!(rows[1].texts[1].as_deref(), Some(""));
+  }
+
+  Ok(())
+}
+
+#[tokio::test]
+async fn convert_pool_parallel_test() -> anyhow::Result<()> {
+  use std::{
+    sync::{
+      atomic::{AtomicUsize, Ordering},
+      Arc,
+    },
+    time::{Duration, Instant},
+  };
+
+  let pool = ConvertPool::new(4);
+  // The running jobs count, and the count of the jobs which see all the jobs running at once.
+  let running = Arc::new(AtomicUsize::new(0));
+  let parallel = Arc::new(AtomicUsize::new(0));
+  let jobs: Vec<_> = (0..4)
+    .map(|_| {
+      let (running, parallel) = (running.clone(), parallel.clone());
+      let (sender, receiver) = oneshot::channel();
+      let job: Job = Box::new(move || {
+        running.fetch_add(1, Ordering::SeqCst);
+        // Wait for the other jobs, the deadline only stops a serial pool from hanging the test.
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while running.load(Ordering::SeqCst) < 4 && Instant::now() < deadline {
+          thread::sleep(Duration::from_millis(1));
+        }
+        if running.load(Ordering::SeqCst) == 4 {
+          parallel.fetch_add(1, Ordering::SeqCst);
+        }
+        let _ = sender.send(vec![]);
+      });
+      pool.sender.lock().unwrap().send(job).unwrap();
+      ConvertJob(receiver)
+    })
+    .collect();
+  for job in jobs {
+    job.rows().await?;
+  }
+
+  // The jobs run in all the workers at once.
+  assert_eq!(parallel.load(Ordering::SeqCst), 4);
+
+  Ok(())
+}